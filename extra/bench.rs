@@ -52,6 +52,18 @@ fn bench_snailx_iter_minimal(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("bytes", |b| {
+        b.iter_batched_ref(
+            snailx::MappedArgs::bytes,
+            |args| {
+                for arg in black_box(args) {
+                    black_box(arg);
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -628,6 +640,19 @@ fn bench_snailx_iter_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched_ref(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                for s in black_box(args) {
+                    black_box(s);
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -672,6 +697,17 @@ fn bench_snailx_nth_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched_ref(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                let _ = black_box(black_box(args).nth(black_box(5)));
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -723,6 +759,19 @@ fn bench_snailx_iter_back_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched_ref(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                while let Some(s) = black_box(black_box(&mut *args).next_back()) {
+                    black_box(s);
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -768,6 +817,17 @@ fn bench_snailx_nth_back_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched_ref(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                let _ = black_box(black_box(args).nth_back(black_box(5)));
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -812,6 +872,17 @@ fn bench_snailx_fold_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                let _ = black_box(black_box(args).fold(0usize, |acc, _| acc + 1));
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
@@ -857,9 +928,220 @@ fn bench_snailx_rfold_preset(c: &mut Criterion) {
         );
     });
 
+    group.bench_function("str_unchecked", |b| {
+        b.iter_batched(
+            // SAFETY: the preset is all valid UTF-8
+            || unsafe { snailx::MappedArgs::utf8_unchecked() },
+            |args| {
+                let _ = black_box(black_box(args).rfold(0usize, |acc, _| acc + 1));
+            },
+            BatchSize::SmallInput
+        );
+    });
+
     group.finish();
 }
 
+// repeated length-dependent conversions, which `CStr` rescans for and `Arg` doesn't
+fn bench_snailx_sized_preset(c: &mut Criterion) {
+    unsafe {
+        snailx::direct::set_argc_argv(
+            ARGV_PRESET_CMDLINE.len() as u32,
+            ARGV_PRESET_CMDLINE.as_ptr()
+        )
+    };
+
+    let mut group = c.benchmark_group("snailx/preset/sized");
+
+    group.bench_function("cstr/len_and_str", |b| {
+        b.iter_batched_ref(
+            snailx::Args::new,
+            |args| {
+                for arg in black_box(args) {
+                    black_box(arg.len());
+                    let _ = black_box(arg.to_str());
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
+    group.bench_function("arg/len_and_str", |b| {
+        b.iter_batched_ref(
+            || snailx::Args::new().sized(),
+            |args| {
+                for arg in black_box(args) {
+                    black_box(arg.len());
+                    let _ = black_box(arg.to_str());
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
+    #[cfg(feature = "std")]
+    group.bench_function("cstr/stdlib_and_os", |b| {
+        b.iter_batched_ref(
+            snailx::Args::new,
+            |args| {
+                for arg in black_box(args) {
+                    black_box(arg.to_stdlib());
+                    black_box(snailx::bench_helpers::to_osstr(arg.as_ptr()));
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
+    #[cfg(feature = "std")]
+    group.bench_function("arg/stdlib_and_os", |b| {
+        b.iter_batched_ref(
+            || snailx::Args::new().sized(),
+            |args| {
+                for arg in black_box(args) {
+                    black_box(arg.to_stdlib());
+                    black_box(arg.to_osstr());
+                }
+            },
+            BatchSize::SmallInput
+        );
+    });
+
+    group.finish();
+}
+
+// the preset's strings back-to-back, like the kernel lays argv out, and the same strings as
+//  separate allocations
+fn contiguous_preset() -> (&'static [*const u8], &'static [*const u8]) {
+    const BLOCK: &[u8] = b"bash\0-c\0pacman -Syu\0||\0echo\0\"failed\"\0&&\0journalctl\0-x\0-e\0";
+
+    let mut block = Vec::new();
+    let mut scattered = Vec::new();
+    let mut start = 0;
+    for (i, &b) in BLOCK.iter().enumerate() {
+        if b == 0 {
+            block.push(BLOCK[start..].as_ptr());
+            scattered.push(Box::leak(BLOCK[start..=i].to_vec().into_boxed_slice()).as_ptr());
+            start = i + 1;
+        }
+    }
+    (Box::leak(block.into_boxed_slice()), Box::leak(scattered.into_boxed_slice()))
+}
+
+// the contiguous argv fast path, which takes lengths from the distances between arguments and
+//  validates them all at once, against the normal path over the same strings
+fn bench_snailx_contiguous_preset(c: &mut Criterion) {
+    let (block, scattered) = contiguous_preset();
+    let argc = block.len() as u32;
+
+    let mut group = c.benchmark_group("snailx/preset/contiguous");
+
+    macro_rules! bench_both {
+        ($name:literal, $block:expr, $scattered:expr, $run:expr) => {
+            group.bench_function(concat!($name, "/block"), |b| {
+                b.iter_batched_ref($block, $run, BatchSize::SmallInput)
+            });
+            group.bench_function(concat!($name, "/scattered"), |b| {
+                b.iter_batched_ref($scattered, $run, BatchSize::SmallInput)
+            });
+        };
+    }
+
+    let str_block =
+        || unsafe { snailx::bench_helpers::checked_contiguous(argc, block.as_ptr()) }.unwrap();
+    let str_scattered =
+        || unsafe { snailx::Args::from_raw(argc, scattered.as_ptr()) }.map_utf8_checked();
+
+    bench_both!("str/iterate", str_block, str_scattered, |args| {
+        for arg in black_box(args) {
+            let _ = black_box(arg);
+        }
+    });
+    bench_both!("str/nth", str_block, str_scattered, |args| {
+        let _ = black_box(black_box(args).nth(black_box(5)));
+    });
+    bench_both!("str/fold", str_block, str_scattered, |args| {
+        black_box(black_box(args).fold(0, |acc, arg| acc + arg.map_or(0, str::len)))
+    });
+
+    group.finish();
+}
+
+extern "C" {
+    #[link_name = "strlen"]
+    fn libc_strlen(s: *const u8) -> usize;
+}
+
+// snailx's strlen implementations against libc's, on a long string. `snailx` is the one in use,
+//  which is libc's unless the `rust_strlen` feature is enabled
+fn bench_snailx_strlen(c: &mut Criterion) {
+    let s = concat!(include_str!("./bee_movie.txt"), "\0").as_ptr();
+
+    let mut group = c.benchmark_group("snailx/strlen");
+
+    group.bench_function("libc", |b| b.iter(|| black_box(unsafe { libc_strlen(black_box(s)) })));
+    group.bench_function("snailx", |b| {
+        b.iter(|| black_box(unsafe { snailx::bench_helpers::strlen(black_box(s)) }))
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| black_box(unsafe { snailx::bench_helpers::strlen_bytes(black_box(s)) }))
+    });
+
+    group.finish();
+}
+
+// snailx's utf-8 validators against std's, on short flags and on long ASCII and multi-byte inputs
+fn bench_snailx_utf8(c: &mut Criterion) {
+    let bee = include_str!("./bee_movie.txt");
+    let multibyte = bee.replace('e', "\u{e9}").replace('o', "\u{20AC}").replace(' ', "\u{1F41D}");
+    let inputs: [(&str, &[u8]); 4] = [
+        ("short", b"-v"),
+        ("flag", b"--output-directory=build"),
+        ("bee_movie", bee.as_bytes()),
+        ("bee_movie_multibyte", multibyte.as_bytes())
+    ];
+
+    #[cfg(target_arch = "x86_64")]
+    let (sse41, avx2) = (is_x86_feature_detected!("sse4.1"), is_x86_feature_detected!("avx2"));
+
+    for &(name, v) in &inputs {
+        let mut group = c.benchmark_group(format!("snailx/utf8/{}", name));
+
+        group.bench_function("std", |b| {
+            b.iter(|| black_box(core::str::from_utf8(black_box(v)).is_ok()))
+        });
+        group.bench_function("snailx", |b| {
+            b.iter(|| black_box(snailx::bench_helpers::is_valid_utf8(black_box(v))))
+        });
+        group.bench_function("scalar", |b| {
+            b.iter(|| black_box(snailx::bench_helpers::validate_utf8_scalar(black_box(v)).is_ok()))
+        });
+        #[cfg(target_arch = "x86_64")]
+        {
+            if sse41 {
+                group.bench_function("sse4.1", |b| {
+                    b.iter(|| {
+                        black_box(unsafe {
+                            snailx::bench_helpers::is_valid_utf8_sse41(black_box(v))
+                        })
+                    })
+                });
+            }
+            if avx2 {
+                group.bench_function("avx2", |b| {
+                    b.iter(|| {
+                        black_box(unsafe {
+                            snailx::bench_helpers::is_valid_utf8_avx2(black_box(v))
+                        })
+                    })
+                });
+            }
+        }
+
+        group.finish();
+    }
+}
+
 fn bench_snailx_helpers(c: &mut Criterion) {
     let mut group = c.benchmark_group("snailx/helpers");
 
@@ -1157,6 +1439,9 @@ pub fn bench(c: &mut Criterion) {
     #[cfg(feature = "rev_iter")]
     bench_snailx_rfold_preset(c);
 
+    bench_snailx_sized_preset(c);
+    bench_snailx_contiguous_preset(c);
+
     #[cfg(feature = "indexing_parser")]
     {
         bench_indexing_parser_minimal(c);
@@ -1164,6 +1449,8 @@ pub fn bench(c: &mut Criterion) {
         bench_indexing_parser_long(c);
     }
 
+    bench_snailx_strlen(c);
+    bench_snailx_utf8(c);
     bench_snailx_helpers(c);
 }
 
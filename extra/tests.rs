@@ -1,13 +1,13 @@
-#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_truncation, clippy::incompatible_msrv)]
 #![allow(clippy::iter_nth_zero)]
 extern crate core;
 extern crate snailx;
 
-use snailx::{CStr, bench_helpers::strlen};
-
-// used as it is a documented safety condition (which tests decide to violate by default) that
-//  `set_argc_argv()` is called while there is no other concurrent access.
-static GLOBAL_LOCK: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+use snailx::{
+    CStr,
+    bench_helpers::strlen,
+    testing::{ArgvGuard, ThreadArgvGuard}
+};
 
 const ARG_SET_0: [*const u8; 0] = [];
 
@@ -51,7 +51,6 @@ const ARG_SET_LONG: [*const u8; 2] = [
     concat!(include_str!("./bee_movie.txt"), "\0").as_ptr()
 ];
 
-#[cfg(feature = "__test_cause_ub")]
 const ARG_SET_BLANK: [*const u8; 1] = ["\0".as_ptr()];
 
 const ARG_SET_NULL: [*const u8; 2] =
@@ -66,50 +65,10 @@ const ARG_SET_UTF8: [[*const u8; 3]; 3] = [
     ["test\0".as_ptr(), b"\xF0\x28\x8C\x28\0".as_ptr(), "hi\0".as_ptr()]
 ];
 
-unsafe fn set_args_empty() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_0.len() as u32, ARG_SET_0.as_ptr());
-    &ARG_SET_0
-}
-
-unsafe fn set_args_one() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_1.len() as u32, ARG_SET_1.as_ptr());
-    &ARG_SET_1
-}
-
-unsafe fn set_args_odd() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_7.len() as u32, ARG_SET_7.as_ptr());
-    &ARG_SET_7
-}
-unsafe fn set_args_even() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_8.len() as u32, ARG_SET_8.as_ptr());
-    &ARG_SET_8
-}
-
-unsafe fn set_args_spec() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_SPEC.len() as u32, ARG_SET_SPEC.as_ptr());
-    &ARG_SET_SPEC
-}
-
-unsafe fn set_args_long() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_LONG.len() as u32, ARG_SET_LONG.as_ptr());
-    &ARG_SET_LONG
-}
-
-#[cfg(feature = "__test_cause_ub")]
-unsafe fn set_args_blank() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_BLANK.len() as u32, ARG_SET_BLANK.as_ptr());
-    &ARG_SET_BLANK
-}
-
-unsafe fn set_args_null() -> &'static [*const u8] {
-    snailx::direct::set_argc_argv(ARG_SET_NULL.len() as u32, ARG_SET_NULL.as_ptr());
-    &ARG_SET_NULL
-}
-
-unsafe fn set_args_utf8(i: usize) -> &'static [*const u8] {
-    let args = &ARG_SET_UTF8[i];
-    snailx::direct::set_argc_argv(args.len() as u32, args.as_ptr());
-    args
+// overrides argv for the current thread only, so tests don't race with each other
+fn with_args<F: FnOnce(&'static [*const u8])>(a: &'static [*const u8], f: F) {
+    let _g = unsafe { ThreadArgvGuard::new(a.len() as u32, a.as_ptr()) };
+    f(a);
 }
 
 // helper macro to run tests on many different sets of arguments. useful for edge cases like
@@ -122,48 +81,19 @@ macro_rules! test_i {
             )*
         }
 
-        // used to prevent panics from deadlocking the testing process
-        struct PanicHandler {}
-        impl PanicHandler {
-            fn new() -> PanicHandler {
-                while GLOBAL_LOCK.compare_exchange(
-                    false,
-                    true,
-                    core::sync::atomic::Ordering::SeqCst,
-                    core::sync::atomic::Ordering::SeqCst
-                ).is_err() {
-                    core::hint::spin_loop();
-                }
-                PanicHandler {}
-            }
-        }
-        impl Drop for PanicHandler {
-            fn drop(&mut self) {
-                GLOBAL_LOCK.store(false, core::sync::atomic::Ordering::SeqCst);
-            }
-        }
-
-        unsafe {
-            let panic_handler = PanicHandler::new();
+        with_args(&ARG_SET_7, test_inner);
+        with_args(&ARG_SET_8, test_inner);
+        with_args(&ARG_SET_0, test_inner);
+        with_args(&ARG_SET_1, test_inner);
 
-            test_inner(set_args_odd());
-            test_inner(set_args_even());
-            test_inner(set_args_empty());
-            test_inner(set_args_one());
+        with_args(&ARG_SET_SPEC, test_inner);
+        with_args(&ARG_SET_LONG, test_inner);
+        with_args(&ARG_SET_BLANK, test_inner);
+        with_args(&ARG_SET_NULL, test_inner);
 
-            test_inner(set_args_spec());
-            test_inner(set_args_long());
-            #[cfg(feature = "__test_cause_ub")]
-            test_inner(set_args_blank());
-            test_inner(set_args_null());
+        with_args(&ARG_SET_UTF8[0], test_inner);
 
-            test_inner(set_args_utf8(0));
-
-            // TODO: use other 2 utf8 sets as well
-
-            // suppress warning and drop the handler to unlock
-            drop(panic_handler);
-        }
+        // TODO: use other 2 utf8 sets as well
     }
 }
 
@@ -179,6 +109,39 @@ fn _strlen_correct() {
     assert_eq!(unsafe { strlen(TEST_STR_2.as_ptr()) }, TEST_STR_2.len() - 1);
 }
 
+#[test]
+fn strlen_impls_match() {
+    use snailx::bench_helpers::strlen_bytes;
+
+    extern "C" {
+        #[link_name = "strlen"]
+        fn libc_strlen(s: *const u8) -> usize;
+    }
+
+    // every length around word and vector sizes, at every alignment
+    let mut buf = [b'x'; 128 + 32];
+    for start in 0..32 {
+        for len in 0..128 {
+            buf[start + len] = 0;
+            let p = unsafe { buf.as_ptr().add(start) };
+            unsafe {
+                assert_eq!(strlen(p), len);
+                assert_eq!(strlen_bytes(p), len);
+                assert_eq!(libc_strlen(p), len);
+            }
+            buf[start + len] = b'x';
+        }
+    }
+
+    // high bytes must not be mistaken for terminators
+    let high = "\u{ff}\u{80}\u{7f}ü\u{10ffff}\0";
+    let p = high.as_ptr();
+    unsafe {
+        assert_eq!(strlen_bytes(p), high.len() - 1);
+        assert_eq!(strlen(p), high.len() - 1);
+    }
+}
+
 // count tests
 
 #[test]
@@ -223,6 +186,115 @@ fn cstrs_correct() {
     }
 }
 
+#[test]
+fn sized_correct() {
+    test_i! {
+        a,
+        let expected = a
+            .iter()
+            .map(|&s| unsafe { CStr::from_ptr(s).to_bytes() })
+            .collect::<Vec<_>>();
+
+        let sized = snailx::Args::new().sized();
+        assert_eq!(sized.len(), a.len());
+        for (arg, &exp) in sized.zip(&expected) {
+            assert_eq!(arg.len(), exp.len());
+            assert_eq!(arg.to_bytes(), exp);
+            assert_eq!(arg.to_bytes_with_nul().last(), Some(&0));
+            assert_eq!(arg.to_stdlib().to_bytes(), exp);
+            assert!(arg == exp);
+        }
+
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(
+            snailx::Args::new().sized().rev().map(|a| a.to_bytes()).collect::<Vec<_>>(),
+            expected.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(snailx::Args::new().sized().count(), a.len());
+        assert_eq!(
+            snailx::Args::new().sized().last().map(|a| a.to_bytes()),
+            expected.last().copied()
+        );
+        assert_eq!(
+            snailx::Args::new().sized().nth(2).map(|a| a.to_bytes()),
+            expected.get(2).copied()
+        );
+        assert_eq!(
+            snailx::Args::new().sized().get(1).map(|a| a.to_bytes()),
+            expected.get(1).copied()
+        );
+    }
+}
+
+#[test]
+#[cfg(not(feature = "assume_valid_str"))]
+fn contiguous_block() {
+    use snailx::bench_helpers::checked_contiguous;
+
+    // laid out like the kernel's argv
+    const BLOCK: &[u8] = "prog\0-v\0\0tw\u{f6} words\0last\0".as_bytes();
+    let starts = [0, 5, 8, 9, 20];
+    let argv = starts.iter().map(|&i| BLOCK[i..].as_ptr()).collect::<Vec<_>>();
+    let expected = ["prog", "-v", "", "tw\u{f6} words", "last"];
+
+    unsafe {
+        let checked = checked_contiguous(argv.len() as u32, argv.as_ptr()).unwrap();
+        assert_eq!(checked.clone().collect::<Result<Vec<_>, _>>().unwrap(), expected);
+        assert_eq!(checked.clone().fold(0, |n, a| n + a.unwrap().len()), 20);
+        assert_eq!(checked.clone().last(), Some(Ok("last")));
+        assert_eq!(checked.clone().nth(3), Some(Ok("tw\u{f6} words")));
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(checked.rev().map(|a| a.unwrap().len()).collect::<Vec<_>>(), [4, 10, 0, 2, 4]);
+
+        // a prefix still reads the next argument's start
+        let checked = checked_contiguous(2, argv.as_ptr()).unwrap();
+        assert_eq!(checked.map(Result::unwrap).collect::<Vec<_>>(), &expected[..2]);
+
+        // a slot rewritten after the block was found, e.g. by `getopt`, isn't trusted
+        let mut slots = argv.clone();
+        let p = slots.as_mut_ptr();
+        let checked = checked_contiguous(5, p).unwrap();
+        p.add(1).write(snailx::cstr!("elsewhere").as_ptr());
+        assert_eq!(
+            checked.map(Result::unwrap).collect::<Vec<_>>(),
+            ["prog", "elsewhere", "", "tw\u{f6} words", "last"]
+        );
+
+        // out of order, or with a gap
+        let swapped = [argv[1], argv[0]];
+        assert!(checked_contiguous(2, swapped.as_ptr()).is_none());
+        let gap = [argv[0], argv[2]];
+        assert!(checked_contiguous(2, gap.as_ptr()).is_none());
+        assert!(checked_contiguous(0, argv.as_ptr()).is_none());
+
+        // contiguous, but one argument isn't UTF-8
+        let bad = b"ok\0\xff\0";
+        let bad = [bad.as_ptr(), bad[3..].as_ptr()];
+        assert!(checked_contiguous(2, bad.as_ptr()).is_none());
+    }
+}
+
+// the program's own argv, which may use the contiguous path, against the normal one.
+//  `process_title` may have retired the block, which just means the normal path is compared
+//  against itself.
+#[test]
+fn contiguous_startup_argv() {
+    let _guard = ArgvGuard::lock();
+    let (argc, argv) = snailx::direct::argc_argv();
+    let args = unsafe { snailx::Args::from_raw(argc, argv) };
+    let expected = args.clone().map(|c| c.to_bytes()).collect::<Vec<_>>();
+
+    let checked = args
+        .map_utf8_checked()
+        .map(|r| r.map(str::as_bytes).map_err(|e| e.arg().to_bytes()))
+        .collect::<Vec<_>>();
+    let valid = expected
+        .iter()
+        .map(|&a| if std::str::from_utf8(a).is_ok() { Ok(a) } else { Err(a) })
+        .collect::<Vec<_>>();
+    assert_eq!(checked, valid);
+}
+
 #[test]
 fn slice_correct() {
     test_i! {
@@ -365,7 +437,15 @@ fn utf8_size_hint() {
         a,
         let args = snailx::MappedArgs::utf8();
 
+        #[cfg(not(feature = "assume_valid_str"))]
         assert_eq!(args.size_hint(), (0, Some(a.len())));
+        // every argument is assumed valid, so `utf8` is exact-size
+        #[cfg(feature = "assume_valid_str")]
+        {
+            assert_eq!(args.size_hint(), (a.len(), Some(a.len())));
+            assert_eq!(args.len(), a.len());
+            assert_eq!(snailx::Args::new().map_utf8().len(), a.len());
+        }
     }
 }
 
@@ -375,11 +455,387 @@ fn os_size_hint_and_len() {
         a,
         let args = snailx::MappedArgs::os();
 
-        #[cfg(not(feature = "infallible_map"))]
+        assert_eq!(args.size_hint(), (a.len(), Some(a.len())));
+        assert_eq!(args.len(), a.len());
+    }
+}
+
+#[test]
+fn infallible_exact_size() {
+    test_i! {
+        a,
+        let mut args = unsafe { snailx::MappedArgs::new_exact(Some) };
+        assert_eq!(args.len(), a.len());
+        assert_eq!(args.exact_len(), Some(a.len()));
+        if args.next().is_some() {
+            assert_eq!(args.len(), a.len() - 1);
+        }
+        assert_eq!(unsafe { snailx::MappedArgs::utf8_unchecked() }.len(), a.len());
+
+        let fallible = snailx::MappedArgs::new(Some);
+        assert_eq!(fallible.size_hint(), (0, Some(a.len())));
+        assert_eq!(fallible.exact_len(), None);
+    }
+}
+
+// the pre-`Fallibility` API still builds, but can't make an iterator infallible
+#[test]
+#[cfg(feature = "infallible_map")]
+#[allow(deprecated)]
+fn infallible_map_compat() {
+    test_i! {
+        a,
+        let args: snailx::MappedArgs<_, _> = unsafe { snailx::MappedArgs::new_infallible(Some) };
+        assert_eq!(args.len(), None);
+        assert_eq!(args.count(), a.len());
+        let args: snailx::MappedArgs<_, _> = snailx::Args::new().map_ty_infallible(Some);
         assert_eq!(args.size_hint(), (0, Some(a.len())));
-        #[cfg(feature = "infallible_map")]
+    }
+}
+
+#[test]
+fn bytes_correct() {
+    test_i! {
+        a,
+        let expected =
+            a.iter().map(|&s| unsafe { CStr::from_ptr(s).to_bytes() }).collect::<Vec<_>>();
+
+        let args = snailx::MappedArgs::bytes();
         assert_eq!(args.size_hint(), (a.len(), Some(a.len())));
+        assert_eq!(args.collect::<Vec<_>>(), expected);
+
+        assert_eq!(snailx::Args::new().map_bytes().nth(1), expected.get(1).copied());
+        assert_eq!(
+            snailx::Args::new().map_bytes().fold(0, |n, b| n + b.len()),
+            expected.iter().map(|b| b.len()).sum::<usize>()
+        );
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(
+            snailx::MappedArgs::bytes().rev().collect::<Vec<_>>(),
+            expected.iter().rev().copied().collect::<Vec<_>>()
+        );
     }
+
+    // invalid UTF-8 is kept
+    with_args(&ARG_SET_UTF8[2], |a| {
+        let args = snailx::MappedArgs::bytes().collect::<Vec<_>>();
+        assert_eq!(args.len(), a.len());
+        assert_eq!(args[1], b"\xF0\x28\x8C\x28");
+    });
+}
+
+// reference chunks from std's validator
+fn std_chunks(mut bytes: &[u8]) -> Vec<(&str, &[u8])> {
+    let mut chunks = Vec::new();
+    while !bytes.is_empty() {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => {
+                chunks.push((s, &[][..]));
+                bytes = &[];
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                let (invalid, rest) = rest.split_at(e.error_len().unwrap_or(rest.len()));
+                chunks.push((std::str::from_utf8(valid).unwrap(), invalid));
+                bytes = rest;
+            }
+        }
+    }
+    chunks
+}
+
+#[test]
+fn utf8_chunks_match_std() {
+    fn check(bytes: &[u8]) {
+        let chunks =
+            snailx::Utf8Chunks::new(bytes).map(|c| (c.valid(), c.invalid())).collect::<Vec<_>>();
+        assert_eq!(chunks, std_chunks(bytes), "{:x?}", bytes);
+    }
+
+    // every one- and two-byte sequence, and three- and four-byte ones around the boundaries
+    for a in 0..=255u8 {
+        check(&[a]);
+        for b in 0..=255u8 {
+            check(&[a, b]);
+        }
+    }
+    let edges = [
+        0x00, 0x41, 0x7F, 0x80, 0x8F, 0x90, 0x9F, 0xA0, 0xBF, 0xC0, 0xC2, 0xDF, 0xE0, 0xED, 0xEF,
+        0xF0, 0xF4, 0xF5, 0xFF
+    ];
+    for &a in &[0xE0, 0xE1, 0xED, 0xEE, 0xF0, 0xF1, 0xF4] {
+        for &b in &edges {
+            for &c in &edges {
+                check(&[a, b, c]);
+                for &d in &edges {
+                    check(&[a, b, c, d]);
+                    check(&[b'x', a, b, c, d, b'y']);
+                }
+            }
+        }
+    }
+    // long enough for the word-at-a-time ASCII path, with errors on either side of it
+    check(b"an ascii run longer than two words\xFFand \xE2\x82\xAC then a cut-off \xF0\x9F\x98");
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn simd_utf8_matches_std() {
+    use snailx::bench_helpers::{is_valid_utf8, is_valid_utf8_avx2, is_valid_utf8_sse41};
+
+    let sse41 = is_x86_feature_detected!("sse4.1");
+    let avx2 = is_x86_feature_detected!("avx2");
+    let check = |bytes: &[u8]| {
+        let expected = std::str::from_utf8(bytes).is_ok();
+        assert_eq!(is_valid_utf8(bytes), expected, "{:x?}", bytes);
+        if sse41 {
+            assert_eq!(unsafe { is_valid_utf8_sse41(bytes) }, expected, "sse4.1 {:x?}", bytes);
+        }
+        if avx2 {
+            assert_eq!(unsafe { is_valid_utf8_avx2(bytes) }, expected, "avx2 {:x?}", bytes);
+        }
+    };
+
+    // sequences at every offset around the 16- and 32-byte block boundaries, and cut off by the
+    //  end of the input
+    let seqs: &[&[u8]] = &[
+        b"\xC3\xA9",
+        b"\xE2\x82\xAC",
+        b"\xF0\x9F\x98\x80",
+        b"\xF4\x8F\xBF\xBF",
+        b"\xFF",
+        b"\x80",
+        b"\xC0\xAF",
+        b"\xC3",
+        b"\xE0\x80\xAF",
+        b"\xE0\xA0",
+        b"\xED\xA0\x80",
+        b"\xF0\x8F\xBF\xBF",
+        b"\xF0\x9F\x98",
+        b"\xF4\x90\x80\x80",
+        b"\xF8\x88\x80\x80\x80",
+        b"\xC3\xA9\xA9"
+    ];
+    for seq in seqs {
+        for at in 0..72 {
+            let mut bytes = vec![b'a'; 72];
+            bytes.splice(at..at, seq.iter().copied());
+            check(&bytes);
+            bytes.truncate(at + seq.len());
+            check(&bytes);
+        }
+    }
+
+    let bee = include_str!("./bee_movie.txt");
+    check(bee.as_bytes());
+    let multibyte = bee.replace('e', "\u{e9}").replace('o', "\u{20AC}").replace(' ', "\u{1F41D}");
+    check(multibyte.as_bytes());
+    let mut broken = multibyte.into_bytes();
+    let mid = broken.len() / 2;
+    broken[mid] = 0xFF;
+    check(&broken);
+}
+
+#[test]
+fn utf8_lossy_keeps_positions() {
+    with_args(&ARG_SET_UTF8[2], |a| {
+        let args = snailx::MappedArgs::utf8_lossy();
+        assert_eq!(args.size_hint(), (a.len(), Some(a.len())));
+
+        let rendered = args.map(|c| c.display().to_string()).collect::<Vec<_>>();
+        let expected = a
+            .iter()
+            .map(|&p| String::from_utf8_lossy(unsafe { CStr::from_ptr(p).to_bytes() }).into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(rendered, expected);
+        assert_eq!(rendered[1], "\u{FFFD}(\u{FFFD}(");
+
+        let chunks = snailx::Args::new().map_utf8_lossy().nth(1).unwrap();
+        assert_eq!(chunks.as_bytes(), b"\xF0\x28\x8C\x28");
+        assert_eq!(chunks.map(|c| c.invalid()).collect::<Vec<_>>(), [&b"\xF0"[..], b"\x8C", b""]);
+    });
+
+    let c = snailx::cstr!("plain");
+    assert_eq!(c.utf8_chunks().map(|c| c.valid()).collect::<Vec<_>>(), ["plain"]);
+    assert_eq!(snailx::Arg::from(c).utf8_chunks().count(), 1);
+    assert_eq!(snailx::cstr!("").utf8_chunks().next(), None);
+}
+
+#[test]
+fn utf8_checked_reports_invalid() {
+    with_args(&ARG_SET_UTF8[1], |a| {
+        let args = snailx::MappedArgs::utf8_checked();
+        assert_eq!(args.len(), a.len());
+
+        let errs = args.map(|r| r.unwrap_err()).collect::<Vec<_>>();
+        for (i, e) in errs.iter().enumerate() {
+            let bytes = unsafe { CStr::from_ptr(a[i]).to_bytes() };
+            let std_err = std::str::from_utf8(bytes).unwrap_err();
+            assert_eq!(e.index(), i);
+            assert_eq!(e.valid_up_to(), std_err.valid_up_to());
+            assert_eq!(e.error_len(), std_err.error_len());
+            assert_eq!(e.arg().to_bytes(), bytes);
+        }
+        assert_eq!((errs[0].valid_up_to(), errs[0].error_len()), (3, Some(1)));
+        assert_eq!((errs[2].valid_up_to(), errs[2].error_len()), (1, Some(1)));
+    });
+
+    with_args(&ARG_SET_UTF8[2], |_| {
+        let args = snailx::MappedArgs::utf8_checked().collect::<Vec<_>>();
+        assert_eq!(args[0], Ok("test"));
+        assert_eq!(args[1].unwrap_err().index(), 1);
+        assert_eq!(args[2], Ok("hi"));
+
+        // indices are argv positions, however far the iterator was advanced
+        let mut rest = snailx::Args::new();
+        rest.next();
+        assert_eq!(rest.map_utf8_checked().next().unwrap().unwrap_err().index(), 1);
+        let mut rest = snailx::Args::new().without_prog().map_utf8_checked();
+        assert_eq!(rest.next().unwrap().unwrap_err().index(), 1);
+
+        // other arguments count from the iterator's start
+        let other = ARG_SET_UTF8[2];
+        let other = unsafe { snailx::Args::from_raw(2, other[1..].as_ptr()) };
+        assert_eq!(other.map_utf8_checked().next().unwrap().unwrap_err().index(), 0);
+        assert_eq!(snailx::MappedArgs::utf8_checked().nth(1).unwrap().unwrap_err().index(), 1);
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(
+            snailx::MappedArgs::utf8_checked().rev().nth(1).unwrap().unwrap_err().index(),
+            1
+        );
+    });
+}
+
+#[test]
+fn utf8_unchecked_matches_utf8() {
+    with_args(&ARG_SET_UTF8[0], |a| {
+        // SAFETY: the first set is all valid UTF-8
+        let args = unsafe { snailx::MappedArgs::utf8_unchecked() };
+        assert_eq!(args.len(), a.len());
+        assert_eq!(args.exact_len(), Some(a.len()));
+        assert!(args.eq(snailx::MappedArgs::utf8()));
+
+        let mut rest = snailx::Args::new();
+        rest.next();
+        assert!(unsafe { rest.map_utf8_unchecked() }.eq(rest.map_utf8()));
+    });
+}
+
+// `utf8` doesn't skip the invalid argument with `assume_valid_str`
+#[cfg(not(feature = "assume_valid_str"))]
+#[test]
+fn indexed_keeps_argv_positions() {
+    with_args(&ARG_SET_UTF8[2], |a| {
+        let valid = [(0, "test"), (2, "hi")];
+        assert_eq!(snailx::MappedArgs::utf8().indexed().collect::<Vec<_>>(), valid);
+        assert_eq!(
+            snailx::MappedArgs::utf8().indexed().fold(Vec::new(), |mut v, x| {
+                v.push(x);
+                v
+            }),
+            valid
+        );
+        assert_eq!(snailx::MappedArgs::utf8().indexed().nth(1), Some((2, "hi")));
+        assert_eq!(snailx::MappedArgs::utf8().indexed().nth(2), None);
+        assert_eq!(snailx::MappedArgs::utf8().indexed().last(), Some((2, "hi")));
+        assert_eq!(snailx::MappedArgs::utf8().indexed().count(), 2);
+        #[cfg(feature = "rev_iter")]
+        {
+            assert_eq!(
+                snailx::MappedArgs::utf8().indexed().rev().collect::<Vec<_>>(),
+                [(2, "hi"), (0, "test")]
+            );
+            assert_eq!(
+                snailx::MappedArgs::utf8().indexed().rfold(0, |acc, (i, _)| acc * 10 + i),
+                20
+            );
+            assert_eq!(snailx::MappedArgs::utf8().indexed().nth_back(1), Some((0, "test")));
+        }
+
+        // the closure sees every index, including those of skipped arguments
+        let seen = std::cell::RefCell::new(Vec::new());
+        let args = snailx::MappedArgs::new_indexed(|i, p| {
+            seen.borrow_mut().push(i);
+            if i % 2 == 0 { Some(p) } else { None }
+        });
+        assert_eq!(args.clone().collect::<Vec<_>>(), [a[0], a[2]]);
+        assert_eq!(*seen.borrow(), [0, 1, 2]);
+        assert_eq!(args.clone().nth(1), Some(a[2]));
+        assert_eq!(args.get(1), None);
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(
+            args.rfold(Vec::new(), |mut v, p| {
+                v.push(p);
+                v
+            }),
+            [a[2], a[0]]
+        );
+
+        // indices are argv positions, however far the iterator was advanced
+        let mut rest = snailx::Args::new();
+        rest.next();
+        let rest = rest.indexed().map(|(i, a)| (i, a.to_bytes().len())).collect::<Vec<_>>();
+        assert_eq!(rest, [(1, 4), (2, 2)]);
+        let rest = snailx::Args::new().without_prog().map_indexed(|i, _| Some(i));
+        assert_eq!(rest.collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(snailx::MappedArgs::utf8().without_prog().indexed().next(), Some((2, "hi")));
+
+        // other arguments count from the iterator's start
+        let other = ARG_SET_UTF8[2];
+        let other = unsafe { snailx::Args::from_raw(2, other[1..].as_ptr()) };
+        assert_eq!(other.indexed().map(|(i, _)| i).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(snailx::Args::new().map_indexed(|i, _| Some(i)).sum::<usize>(), 3);
+
+        let args = unsafe { snailx::MappedArgs::new_indexed_infallible(|i, _| Some(i)) };
+        assert_eq!(args.exact_len(), Some(3));
+        assert_eq!(args.len(), 3);
+        assert_eq!(args.clone().nth(2), Some(2));
+        assert_eq!(args.sum::<usize>(), 3);
+        assert_eq!(snailx::Args::new().indexed().size_hint(), (3, Some(3)));
+    });
+}
+
+#[test]
+fn split_at_double_dash() {
+    const ARGS: [*const u8; 7] = [
+        "prog\0".as_ptr(),
+        "--v\0".as_ptr(),
+        "-\0".as_ptr(),
+        "---\0".as_ptr(),
+        "--\0".as_ptr(),
+        "a\0".as_ptr(),
+        "--\0".as_ptr()
+    ];
+
+    with_args(&ARGS, |_| {
+        let (opts, rest) = snailx::Args::new().without_prog().split_at_double_dash();
+        assert_eq!(opts.map_utf8().collect::<Vec<_>>(), ["--v", "-", "---"]);
+        assert_eq!(rest.unwrap().map_utf8().collect::<Vec<_>>(), ["a", "--"]);
+
+        let (opts, rest) = snailx::MappedArgs::utf8().without_prog().split_at_double_dash();
+        assert_eq!(opts.collect::<Vec<_>>(), ["--v", "-", "---"]);
+        assert_eq!(rest.unwrap().collect::<Vec<_>>(), ["a", "--"]);
+
+        // a trailing `--` leaves an empty rest
+        let mut args = snailx::Args::new();
+        args.nth(4);
+        let (opts, rest) = args.split_at_double_dash();
+        assert_eq!(opts.len(), 1);
+        assert_eq!(rest.unwrap().len(), 0);
+    });
+
+    with_args(&ARG_SET_UTF8[0], |_| {
+        let (opts, rest) = snailx::Args::new().split_at_double_dash();
+        assert_eq!(opts.len(), 3);
+        assert!(rest.is_none());
+        assert_eq!(snailx::MappedArgs::utf8().without_prog().collect::<Vec<_>>(), ["one", "hi"]);
+    });
+
+    with_args(&ARG_SET_0, |_| {
+        assert_eq!(snailx::Args::new().without_prog().len(), 0);
+        assert_eq!(snailx::MappedArgs::bytes().without_prog().next(), None);
+        assert_eq!(snailx::Args::new().split_at_double_dash(), (snailx::Args::new(), None));
+    });
 }
 
 // TODO: test all utf8 tests with test_utf8, not just test_i/first 2 utf8 sets
@@ -395,10 +851,8 @@ macro_rules! test_utf8 {
             )*
         }
 
-        unsafe {
-            test_inner(true, set_args_utf8(0));
-            test_inner(false, set_args_utf8(1));
-        }
+        with_args(&ARG_SET_UTF8[0], |a| test_inner(true, a));
+        with_args(&ARG_SET_UTF8[1], |a| test_inner(false, a));
     };
 }
 
@@ -541,29 +995,29 @@ fn utf8_nth_no_invalid() {
 #[cfg(not(feature = "assume_valid_str"))]
 #[test]
 fn utf8_skips_invalid() {
-    let a = unsafe { set_args_utf8(2) };
-
-    let mut args = snailx::MappedArgs::utf8();
+    with_args(&ARG_SET_UTF8[2], |a| {
+        let mut args = snailx::MappedArgs::utf8();
 
-    assert_eq!(
-        args.next(),
-        Some(unsafe {
-            snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
-                a[0],
-                strlen(a[0])
-            )))
-        })
-    );
-    assert_eq!(
-        args.next(),
-        Some(unsafe {
-            snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
-                a[2],
-                strlen(a[2])
-            )))
-        })
-    );
-    assert!(args.next().is_none());
+        assert_eq!(
+            args.next(),
+            Some(unsafe {
+                snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    a[0],
+                    strlen(a[0])
+                )))
+            })
+        );
+        assert_eq!(
+            args.next(),
+            Some(unsafe {
+                snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    a[2],
+                    strlen(a[2])
+                )))
+            })
+        );
+        assert!(args.next().is_none());
+    });
 }
 
 // reversed iteration tests
@@ -795,29 +1249,29 @@ fn utf8_nth_no_invalid_back() {
 #[cfg(all(not(feature = "assume_valid_str"), feature = "rev_iter"))]
 #[test]
 fn utf8_skips_invalid_back() {
-    let a = unsafe { set_args_utf8(2) };
-
-    let mut args = snailx::MappedArgs::utf8();
+    with_args(&ARG_SET_UTF8[2], |a| {
+        let mut args = snailx::MappedArgs::utf8();
 
-    assert_eq!(
-        args.next_back(),
-        Some(unsafe {
-            snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
-                a[2],
-                strlen(a[2])
-            )))
-        })
-    );
-    assert_eq!(
-        args.next_back(),
-        Some(unsafe {
-            snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
-                a[0],
-                strlen(a[0])
-            )))
-        })
-    );
-    assert!(args.next_back().is_none());
+        assert_eq!(
+            args.next_back(),
+            Some(unsafe {
+                snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    a[2],
+                    strlen(a[2])
+                )))
+            })
+        );
+        assert_eq!(
+            args.next_back(),
+            Some(unsafe {
+                snailx::switch!(core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    a[0],
+                    strlen(a[0])
+                )))
+            })
+        );
+        assert!(args.next_back().is_none());
+    });
 }
 
 // fold/rfold tests
@@ -902,3 +1356,851 @@ fn utf8_rfold_collect_correct() {
 }
 
 // TODO: test parser
+
+// environment tests
+
+const ENV_SET: [*const u8; 5] = [
+    "HOME=/home/snail\0".as_ptr(),
+    "EMPTY=\0".as_ptr(),
+    "NO_SEPARATOR\0".as_ptr(),
+    "EQ=a=b\0".as_ptr(),
+    core::ptr::null()
+];
+
+const ENV_SET_UTF8: [*const u8; 4] =
+    [b"BAD=\xFF\0".as_ptr(), b"\xFF=bad\0".as_ptr(), "GOOD=yes\0".as_ptr(), core::ptr::null()];
+
+// envp is only ever overridden by these tests, so a separate lock is enough
+static ENV_LOCK: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+
+struct EnvGuard(*const *const u8);
+impl EnvGuard {
+    fn new(envp: *const *const u8) -> EnvGuard {
+        while ENV_LOCK
+            .compare_exchange(
+                false,
+                true,
+                core::sync::atomic::Ordering::SeqCst,
+                core::sync::atomic::Ordering::SeqCst
+            )
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        EnvGuard(unsafe { snailx::direct::set_envp(envp) })
+    }
+}
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        unsafe { snailx::direct::set_envp(self.0) };
+        ENV_LOCK.store(false, core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn env_pairs_correct() {
+    let _g = EnvGuard::new(ENV_SET.as_ptr());
+
+    let pairs = snailx::Env::new()
+        .map(|(k, v)| (k, v.to_stdlib().to_bytes()))
+        .collect::<Vec<(&[u8], &[u8])>>();
+    assert_eq!(
+        pairs,
+        [
+            (&b"HOME"[..], &b"/home/snail"[..]),
+            (b"EMPTY", b""),
+            (b"NO_SEPARATOR", b""),
+            (b"EQ", b"a=b")
+        ]
+    );
+}
+
+#[test]
+fn env_get() {
+    let _g = EnvGuard::new(ENV_SET.as_ptr());
+
+    assert_eq!(snailx::Env::get("HOME").unwrap().to_stdlib().to_bytes(), b"/home/snail");
+    assert_eq!(snailx::Env::get("EQ").unwrap().to_stdlib().to_bytes(), b"a=b");
+    assert_eq!(snailx::Env::get("EMPTY").unwrap().to_stdlib().to_bytes(), b"");
+    assert!(snailx::Env::get("HOM").is_none());
+    assert!(snailx::Env::get("MISSING").is_none());
+}
+
+#[test]
+fn env_null() {
+    let _g = EnvGuard::new(core::ptr::null());
+
+    assert_eq!(snailx::Env::new().count(), 0);
+    assert_eq!(snailx::MappedEnv::utf8().count(), 0);
+    assert!(snailx::Env::get("HOME").is_none());
+}
+
+#[cfg(not(feature = "assume_valid_str"))]
+#[test]
+fn env_utf8_skips_invalid() {
+    let _g = EnvGuard::new(ENV_SET_UTF8.as_ptr());
+
+    assert_eq!(snailx::MappedEnv::utf8().collect::<Vec<_>>(), [("GOOD", "yes")]);
+    assert_eq!(snailx::Env::new().count(), 3);
+}
+
+#[test]
+fn env_os() {
+    let _g = EnvGuard::new(ENV_SET.as_ptr());
+
+    let os = snailx::MappedEnv::os().collect::<Vec<_>>();
+    let utf8 = snailx::Env::new().map_utf8().collect::<Vec<_>>();
+    assert_eq!(os.len(), utf8.len());
+    for ((ok, ov), (uk, uv)) in os.into_iter().zip(utf8) {
+        assert_eq!(ok, uk);
+        assert_eq!(ov, uv);
+    }
+}
+
+// argv source tests
+
+#[test]
+fn argv_source_override() {
+    let _g = unsafe { ArgvGuard::new(ARG_SET_1.len() as u32, ARG_SET_1.as_ptr()) };
+
+    assert_eq!(snailx::direct::argv_source(), snailx::direct::ArgvSource::Override);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn cmdline_fallback_split() {
+    let split = |buf: &[u8], cap: usize| {
+        let mut ptrs = vec![core::ptr::null(); cap];
+        let n = snailx::bench_helpers::split_cmdline(buf, &mut ptrs);
+        ptrs[..n]
+            .iter()
+            .map(|&p| unsafe { CStr::from_ptr(p) }.to_bytes().to_vec())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(split(b"prog\0--flag\0\0value\0", 8), [&b"prog"[..], b"--flag", b"", b"value"]);
+    // cut off by the buffer's capacity
+    assert_eq!(split(b"prog\0--fl", 8), [b"prog"]);
+    // more arguments than pointers
+    assert_eq!(split(b"a\0b\0c\0", 2), [b"a", b"b"]);
+    assert!(split(b"", 8).is_empty());
+
+    let cmdline = std::fs::read("/proc/self/cmdline").unwrap();
+    let expected =
+        std::env::args_os().map(std::os::unix::ffi::OsStringExt::into_vec).collect::<Vec<_>>();
+    assert_eq!(split(&cmdline, snailx::direct::CMDLINE_ARGS_CAP), expected);
+}
+
+// override guard tests
+
+#[test]
+fn argv_guard_restores() {
+    let _lock = ArgvGuard::lock();
+    let before = snailx::direct::argc_argv();
+    {
+        let _g = unsafe { ArgvGuard::new(ARG_SET_7.len() as u32, ARG_SET_7.as_ptr()) };
+        assert_eq!(snailx::direct::argc_argv(), (7, ARG_SET_7.as_ptr()));
+    }
+    assert_eq!(snailx::direct::argc_argv(), before);
+}
+
+#[test]
+fn thread_argv_guard_isolated() {
+    with_args(&ARG_SET_7, |a| {
+        assert_eq!(snailx::Args::new().len(), a.len());
+        // other threads don't see this thread's override
+        let other = std::thread::spawn(|| snailx::direct::argc_argv().1 as usize).join().unwrap();
+        assert_ne!(other, a.as_ptr() as usize);
+
+        with_args(&ARG_SET_1, |b| {
+            assert_eq!(snailx::MappedArgs::utf8().collect::<Vec<_>>(), ["bash"]);
+            assert_eq!(snailx::direct::argc_argv(), (1, b.as_ptr()));
+        });
+        assert_eq!(snailx::direct::argc_argv(), (7, a.as_ptr()));
+    });
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_respects_thread_override() {
+    use snailx::indexing_parser::{IndexingParser, OptRule};
+
+    const ARGS: [*const u8; 3] = ["prog\0".as_ptr(), "-v\0".as_ptr(), "input\0".as_ptr()];
+
+    with_args(&ARGS, |_| {
+        let mut parser = IndexingParser::new();
+        parser
+            .parse(&[OptRule::new_auto("verbose")], 0..=1, &[("input", 0)], |s| s == "prog", false)
+            .unwrap();
+
+        assert_eq!(parser.prog_name(), Some("prog"));
+        assert!(parser.flag("verbose"));
+        assert_eq!(parser.named_positional("input"), Ok("input"));
+    });
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_sized_values() {
+    use snailx::indexing_parser::{IndexingParser, OptRule};
+
+    const ARGS: [*const u8; 5] = [
+        "prog\0".as_ptr(),
+        "--name=xyz\0".as_ptr(),
+        "--pair\0".as_ptr(),
+        "a\0".as_ptr(),
+        "bc\0".as_ptr()
+    ];
+
+    with_args(&ARGS, |_| {
+        let mut parser = IndexingParser::new();
+        parser
+            .parse(
+                &[
+                    OptRule::new_auto_long("pair").set_val_count(2),
+                    OptRule::new_auto_long("name").set_val_count(1)
+                ],
+                ..,
+                &[],
+                |s| s == "prog",
+                false
+            )
+            .unwrap();
+
+        let pair = parser.option("pair").unwrap().sized();
+        assert_eq!(pair.len(), 2);
+        assert_eq!(pair.get(1).unwrap().len(), 2);
+        assert_eq!(
+            pair.map(|a| (a.len(), a.to_str().unwrap())).collect::<Vec<_>>(),
+            [(1, "a"), (2, "bc")]
+        );
+
+        let name = parser.option("name").unwrap().sized().next().unwrap();
+        assert!(name == "xyz");
+        assert_eq!(name.len(), 3);
+    });
+}
+
+#[test]
+fn argc_argv_pairs_consistent() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static DONE: AtomicBool = AtomicBool::new(false);
+
+    // the guard keeps other tests from overriding argv while the writer below does
+    let _g = unsafe { ArgvGuard::new(ARG_SET_7.len() as u32, ARG_SET_7.as_ptr()) };
+    DONE.store(false, Ordering::SeqCst);
+
+    let readers = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                while !DONE.load(Ordering::Relaxed) {
+                    // consts don't have stable addresses, so the sets are told apart by content
+                    let (argc, argv) = snailx::direct::argc_argv();
+                    let first = unsafe { CStr::from_ptr(argv.read()) };
+                    match first.to_stdlib().to_bytes() {
+                        b"some" => assert_eq!(argc, 7),
+                        b"bash" => assert_eq!(argc, 1),
+                        other => panic!("unexpected argv[0]: {:?}", other)
+                    }
+
+                    let args = snailx::Args::new();
+                    let len = args.len();
+                    assert_eq!(args.count(), len);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..100_000 {
+        let set: &'static [*const u8] = if i % 2 == 0 { &ARG_SET_1 } else { &ARG_SET_7 };
+        unsafe { snailx::direct::set_argc_argv(set.len() as u32, set.as_ptr()) };
+    }
+    DONE.store(true, Ordering::Relaxed);
+
+    for r in readers {
+        r.join().unwrap();
+    }
+}
+
+// macro tests
+
+#[test]
+fn cstr_macro() {
+    const EMPTY: CStr<'static> = snailx::cstr!("");
+
+    assert_eq!(snailx::cstr!("hello").to_stdlib().to_bytes_with_nul(), b"hello\0");
+    assert_eq!(snailx::cstr!("ünïcode").len(), "ünïcode".len());
+    assert_eq!(EMPTY.len(), 0);
+}
+
+#[test]
+#[allow(clippy::comparison_to_empty)]
+fn cstr_api() {
+    let c = snailx::cstr!("--Name=välue=x");
+    let empty = snailx::cstr!("");
+    let invalid = unsafe { CStr::from_bytes_with_nul_unchecked(b"a\xffb\0") };
+
+    assert_eq!(c.to_bytes(), "--Name=välue=x".as_bytes());
+    assert_eq!(c.to_bytes_with_nul(), "--Name=välue=x\0".as_bytes());
+    assert_eq!(c.to_str(), Ok("--Name=välue=x"));
+    assert_eq!(invalid.to_str().unwrap_err().valid_up_to(), 1);
+    #[cfg(feature = "alloc")]
+    assert_eq!(invalid.to_string_lossy(), "a\u{FFFD}b");
+    assert!(empty.is_empty() && !c.is_empty());
+    assert_eq!(empty.to_bytes_with_nul(), b"\0");
+
+    assert!(c.starts_with(b"") && c.starts_with(b"--N") && !c.starts_with(b"--n"));
+    assert!(!empty.starts_with(b"\0") && !c.starts_with("--Name=välue=x\0".as_bytes()));
+    assert_eq!(c.strip_prefix(b"--Name=").unwrap(), "välue=x");
+    assert_eq!(c.strip_prefix("--Name=välue=x".as_bytes()).unwrap(), "");
+    assert!(c.strip_prefix("--Name=välue=xy".as_bytes()).is_none());
+
+    let (k, v) = c.split_once(b'=').unwrap();
+    assert_eq!((k, v.to_bytes()), (&b"--Name"[..], "välue=x".as_bytes()));
+    assert!(c.split_once(b':').is_none() && c.split_once(0).is_none());
+    assert!(empty.split_once(b'=').is_none());
+
+    assert!(c.eq_ignore_ascii_case("--NAME=välue=X".as_bytes()));
+    assert!(!c.eq_ignore_ascii_case("--NAME=VÄLUE=X".as_bytes()));
+    assert!(!c.eq_ignore_ascii_case(b"--name") && !c.eq_ignore_ascii_case(b""));
+    assert!(empty.eq_ignore_ascii_case(b""));
+
+    assert!(c == "--Name=välue=x" && c == *"--Name=välue=x");
+    assert!(c == "--Name=välue=x".as_bytes() && c == *"--Name=välue=x".as_bytes());
+    assert!(c != "--Name" && c != "--Name=välue=x!" && c != "--Name=välue=x\0");
+    assert!(empty == "" && empty != "\0");
+}
+
+#[test]
+fn cstr_display_and_debug() {
+    let c = unsafe { CStr::from_bytes_with_nul_unchecked(b"a\tb\"c'\xffd\xe2\x82\0") };
+    let arg = snailx::Arg::from(c);
+
+    assert_eq!(c.display().to_string(), "a\tb\"c'\u{FFFD}d\u{FFFD}");
+    #[cfg(feature = "alloc")]
+    assert_eq!(c.display().to_string(), c.to_string_lossy());
+    assert_eq!(c.escape_debug().to_string(), r#"a\tb\"c'\xffd\xe2\x82"#);
+    assert_eq!(format!("{:?}", c), r#""a\tb\"c'\xffd\xe2\x82""#);
+    assert_eq!(format!("{:?}", arg), format!("{:?}", c));
+    assert_eq!(arg.display().to_string(), c.display().to_string());
+
+    // valid strings look the same as `str`'s `Debug`
+    let valid = snailx::cstr!("it's \"quoted\"\n\u{1}ü");
+    assert_eq!(format!("{:?}", valid), format!("{:?}", "it's \"quoted\"\n\u{1}ü"));
+    assert_eq!(valid.display().to_string(), "it's \"quoted\"\n\u{1}ü");
+    assert_eq!(format!("{:?}", snailx::ByContent(valid)), format!("ByContent({:?})", valid));
+    assert_eq!(snailx::cstr!("").display().to_string(), "");
+
+    // valid strings are padded like `str`, invalid ones aren't
+    let ab = snailx::cstr!("ab").display();
+    assert_eq!(format!("{:>4}|{:-<3}|{:.1}|{:^6}", ab, ab, ab, ab), "  ab|ab-|a|  ab  ");
+    assert_eq!(format!("{:>10}", valid.display()), format!("{:>10}", "it's \"quoted\"\n\u{1}ü"));
+    assert_eq!(format!("{:4}", snailx::cstr!("").display()), "    ");
+    assert_eq!(format!("{:>20}", c.display()), c.display().to_string());
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_debug_values() {
+    use snailx::indexing_parser::{IndexingParser, OptRule};
+
+    const ARGS: [*const u8; 3] = ["prog\0".as_ptr(), "--name\0".as_ptr(), "a\"b\tc\0".as_ptr()];
+
+    with_args(&ARGS, |_| {
+        let mut parser = IndexingParser::new();
+        parser
+            .parse(
+                &[OptRule::new_auto_long("name").set_val_count(1)],
+                ..,
+                &[],
+                |s| s == "prog",
+                false
+            )
+            .unwrap();
+
+        assert_eq!(format!("{:?}", parser), r#"IndexingParser(program="prog", name=["a\"b\tc"])"#);
+    });
+}
+
+#[test]
+fn by_content() {
+    use {
+        core::cmp::Ordering,
+        snailx::{Arg, ByContent},
+        std::collections::{BTreeSet, HashSet}
+    };
+
+    let a = snailx::cstr!("--flag");
+    // a separate copy, since identical literals may be merged
+    let owned = b"--flag\0".to_vec();
+    let b = unsafe { CStr::from_bytes_with_nul_unchecked(&owned) };
+    let longer = snailx::cstr!("--flags");
+    let empty = snailx::cstr!("");
+
+    assert_ne!(a, b);
+    assert!(a.content_eq(&b) && !a.content_eq(&longer) && !longer.content_eq(&a));
+    assert_eq!(a.content_cmp(&longer), Ordering::Less);
+    assert_eq!(longer.content_cmp(&a), Ordering::Greater);
+    assert_eq!(empty.content_cmp(&a), Ordering::Less);
+    assert_eq!(snailx::cstr!("\u{ff}").content_cmp(&snailx::cstr!("a")), Ordering::Greater);
+
+    assert_eq!(ByContent(a), ByContent(b));
+    assert_eq!(ByContent(Arg::from(a)), ByContent(Arg::from(b)));
+    assert_eq!(Arg::from(a).content_cmp(&Arg::from(longer)), a.content_cmp(&longer));
+
+    let set: HashSet<_> = [a, b, longer, empty].iter().copied().map(ByContent).collect();
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&ByContent(b)));
+
+    let sorted: Vec<&[u8]> = [longer, b, empty, a]
+        .iter()
+        .map(|&c| ByContent(c))
+        .collect::<BTreeSet<_>>()
+        .iter()
+        .map(|c| c.to_bytes())
+        .collect();
+    assert_eq!(sorted, [&b""[..], b"--flag", b"--flags"]);
+}
+
+#[test]
+fn argv_macro() {
+    let empty: &[*const u8; 0] = snailx::argv![];
+    assert!(empty.is_empty());
+
+    with_args(snailx::argv!["prog", "", "ünïcode",], |a| {
+        assert_eq!(a.len(), 3);
+        assert_eq!(snailx::MappedArgs::utf8().collect::<Vec<_>>(), ["prog", "", "ünïcode"]);
+    });
+}
+
+#[test]
+fn args_from_raw() {
+    let argv = snailx::argv!["a", "bc"];
+    let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+
+    assert_eq!(args.as_slice(), &argv[..]);
+}
+
+// startup hook tests
+
+static HOOK_RUNS: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+static HOOK_SAW_ARGS: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+
+snailx::on_startup! {
+    fn hook_count() {
+        HOOK_RUNS.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Checks that snailx's own hook already ran.
+    pub(crate) fn hook_args() {
+        HOOK_RUNS.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+        // only glibc passes argc/argv/envp to init hooks
+        let init = cfg!(not(target_env = "gnu"))
+            || (snailx::direct::argv_source() == snailx::direct::ArgvSource::Init
+                && snailx::Env::new().next().is_some());
+        HOOK_SAW_ARGS.store(
+            snailx::Args::new().len() != 0 && init,
+            core::sync::atomic::Ordering::SeqCst
+        );
+    }
+}
+
+snailx::on_startup! {
+    fn hook_separate() {
+        HOOK_RUNS.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn startup_hooks_ran() {
+    assert_eq!(HOOK_RUNS.load(core::sync::atomic::Ordering::SeqCst), 3);
+    assert!(HOOK_SAW_ARGS.load(core::sync::atomic::Ordering::SeqCst));
+}
+
+// program name tests
+
+#[cfg(not(feature = "assume_valid_str"))]
+#[test]
+fn prog_name() {
+    with_args(snailx::argv!["/usr/local/bin/snail", "x"], |_| {
+        assert_eq!(snailx::prog_name().unwrap().to_stdlib().to_bytes(), b"snail");
+        assert_eq!(snailx::prog_name_str(), Some("snail"));
+        assert!(!snailx::is_login_shell());
+    });
+    with_args(snailx::argv!["-bash"], |_| {
+        assert_eq!(snailx::prog_name_str(), Some("-bash"));
+        assert!(snailx::is_login_shell());
+    });
+    with_args(snailx::argv!["trailing/"], |_| {
+        assert_eq!(snailx::prog_name_str(), Some(""));
+    });
+    with_args(&ARG_SET_UTF8[1], |_| {
+        assert!(snailx::prog_name().is_some());
+        assert!(snailx::prog_name_str().is_none());
+    });
+    with_args(&ARG_SET_0, |_| {
+        assert!(snailx::prog_name().is_none());
+        assert!(snailx::prog_name_str().is_none());
+        assert!(!snailx::is_login_shell());
+    });
+}
+
+#[test]
+fn exe_path() {
+    let exe = std::env::current_exe().unwrap();
+    let path = snailx::exe_path().unwrap();
+    let path = std::path::Path::new(path.to_stdlib().to_str().unwrap());
+
+    assert_eq!(path.file_name(), exe.file_name());
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_is_exe_name() {
+    use snailx::indexing_parser::is_exe_name;
+
+    let exe = std::env::current_exe().unwrap();
+    let name: &'static str =
+        Box::leak(exe.file_name().unwrap().to_str().unwrap().to_owned().into_boxed_str());
+    let login: &'static str = Box::leak(format!("-{}", name).into_boxed_str());
+
+    assert!(is_exe_name(name));
+    assert!(is_exe_name(login));
+    assert!(!is_exe_name("definitely-not-the-test-binary"));
+}
+
+// response file tests
+
+// writes `contents` to a uniquely named temporary file, returning its path and a leaked `@path\0`
+#[cfg(feature = "alloc")]
+fn response_file(name: &str, contents: &[u8]) -> (String, *const u8) {
+    let path = std::env::temp_dir()
+        .join(format!("snailx-{}-{}", std::process::id(), name))
+        .to_str()
+        .unwrap()
+        .to_owned();
+    std::fs::write(&path, contents).unwrap();
+    let arg: &'static [u8] = Box::leak(format!("@{}\0", path).into_bytes().into_boxed_slice());
+    (path, arg.as_ptr())
+}
+
+#[cfg(feature = "alloc")]
+fn raw_args(argv: Vec<*const u8>) -> snailx::Args {
+    let argv: &'static [*const u8] = Box::leak(argv.into_boxed_slice());
+    unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn response_files_expand() {
+    let (_, inner) = response_file("inner.rsp", b"from-inner\n");
+    let (_, outer) = response_file(
+        "outer.rsp",
+        format!(
+            "# leading comment\n--flag 'single quoted' \"double \\\"quoted\\\" $x\"\nesc\\ aped \
+             '' a#b # trailing comment\n{} @\n",
+            unsafe { CStr::from_ptr(inner).to_stdlib().to_str().unwrap() }
+        )
+        .as_bytes()
+    );
+
+    let args = raw_args(vec!["prog\0".as_ptr(), outer, "last\0".as_ptr()])
+        .expand_response_files()
+        .unwrap();
+    let args: Vec<&str> = args.map_utf8().collect();
+    assert_eq!(
+        args,
+        [
+            "prog",
+            "--flag",
+            "single quoted",
+            "double \"quoted\" $x",
+            "esc aped",
+            "",
+            "a#b",
+            "from-inner",
+            "@",
+            "last"
+        ]
+    );
+}
+
+// `\` and a newline are removed, like in a shell, so they never produce an empty argument
+#[cfg(feature = "alloc")]
+#[test]
+fn response_files_line_continuation() {
+    let (_, arg) = response_file("continued.rsp", b"a \\\n  b jo\\\nined \\\n");
+    let args = raw_args(vec![arg]).expand_response_files().unwrap();
+    assert_eq!(args.map_utf8().collect::<Vec<_>>(), ["a", "b", "joined"]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn response_files_none() {
+    let args = raw_args(vec!["prog\0".as_ptr(), "@\0".as_ptr(), "a@b\0".as_ptr()]);
+    let expanded = args.expand_response_files().unwrap();
+    assert_eq!(expanded, args);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn response_files_errors() {
+    use snailx::ResponseFileErrorKind::*;
+
+    let missing = std::env::temp_dir().join("snailx-definitely-missing.rsp");
+    let missing_arg: &'static [u8] =
+        Box::leak(format!("@{}\0", missing.to_str().unwrap()).into_bytes().into_boxed_slice());
+    let err = raw_args(vec![missing_arg.as_ptr()]).expand_response_files().unwrap_err();
+    assert_eq!(err.kind(), Unreadable);
+    assert_eq!(err.path(), missing.to_str().unwrap().as_bytes());
+    assert_eq!(err.line(), None);
+
+    let (path, arg) = response_file("quote.rsp", b"ok\n'fine'\n\"never\nclosed\n");
+    let err = raw_args(vec![arg]).expand_response_files().unwrap_err();
+    assert_eq!(err.kind(), UnterminatedQuote);
+    assert_eq!(err.path(), path.as_bytes());
+    assert_eq!(err.line(), Some(3));
+    assert_eq!(err.to_string(), format!("{}:3: unterminated quote", path));
+
+    let (_, arg) = response_file("nul.rsp", b"ok\nbad\0arg\n");
+    let err = raw_args(vec![arg]).expand_response_files().unwrap_err();
+    assert_eq!((err.kind(), err.line()), (InteriorNul, Some(2)));
+
+    // references itself on its second line
+    let (path, _) = response_file("loop.rsp", b"");
+    std::fs::write(&path, format!("a\n@{}\n", path)).unwrap();
+    let arg: &'static [u8] = Box::leak(format!("@{}\0", path).into_bytes().into_boxed_slice());
+    let err = raw_args(vec![arg.as_ptr()]).expand_response_files().unwrap_err();
+    assert_eq!(err.kind(), TooDeep);
+    assert_eq!(err.path(), path.as_bytes());
+    assert_eq!(err.line(), Some(2));
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_expands_response_files() {
+    use snailx::indexing_parser::{Error, IndexingParser, OptRule};
+
+    let (_, rsp) = response_file("parser.rsp", b"-v\ninput\n");
+    let argv: &'static [*const u8] = Box::leak(vec!["prog\0".as_ptr(), rsp].into_boxed_slice());
+
+    with_args(argv, |_| {
+        let rules = [OptRule::new_auto("verbose")];
+
+        let mut parser = IndexingParser::new();
+        parser.set_expand_response_files(true);
+        parser.parse(&rules, 0..=1, &[("input", 0)], |s| s == "prog", false).unwrap();
+        assert!(parser.flag("verbose"));
+        assert_eq!(parser.named_positional("input"), Ok("input"));
+
+        // without the switch, the argument is just a positional
+        let mut parser = IndexingParser::new();
+        parser.parse(&rules, 0..=1, &[("input", 0)], |s| s == "prog", false).unwrap();
+        assert!(!parser.flag("verbose"));
+        assert!(parser.named_positional("input").unwrap().starts_with('@'));
+    });
+
+    let missing: &'static [u8] = b"@/nonexistent/snailx.rsp\0";
+    let argv: &'static [*const u8] =
+        Box::leak(vec!["prog\0".as_ptr(), missing.as_ptr()].into_boxed_slice());
+    with_args(argv, |_| {
+        let mut parser = IndexingParser::new();
+        parser.set_expand_response_files(true);
+        match parser.parse(&[], .., &[], |s| s == "prog", false) {
+            Err(Error::ResponseFile(e)) => assert_eq!(e.path(), b"/nonexistent/snailx.rsp"),
+            r => panic!("expected a response file error, got {:?}", r)
+        }
+    });
+}
+
+#[cfg(feature = "indexing_parser")]
+#[test]
+fn parser_assume_utf8() {
+    use snailx::indexing_parser::{IndexingParser, OptRule};
+
+    const ARGS: [*const u8; 4] =
+        ["prog\0".as_ptr(), "-v\0".as_ptr(), "--name=Łukasz\0".as_ptr(), "in\0".as_ptr()];
+
+    with_args(&ARGS, |_| {
+        let mut parser = IndexingParser::new();
+        // SAFETY: every argument above is valid UTF-8
+        unsafe { parser.set_assume_utf8(true) };
+        parser
+            .parse(
+                &[OptRule::new_auto("verbose"), OptRule::new_auto_long("name").set_val_count(1)],
+                0..=1,
+                &[("input", 0)],
+                |s| s == "prog",
+                false
+            )
+            .unwrap();
+
+        assert_eq!(parser.prog_name(), Some("prog"));
+        assert!(parser.flag("verbose"));
+        assert_eq!(parser.option("name").unwrap().collect::<Vec<_>>(), ["Łukasz"]);
+        assert_eq!(parser.named_positional("input"), Ok("in"));
+    });
+}
+
+#[test]
+fn empty_args() {
+    const ARGS: [*const u8; 4] = ["prog\0".as_ptr(), "\0".as_ptr(), "x\0".as_ptr(), "\0".as_ptr()];
+
+    with_args(&ARGS, |_| {
+        assert_eq!(snailx::MappedArgs::utf8().collect::<Vec<_>>(), ["prog", "", "x", ""]);
+        assert_eq!(snailx::MappedArgs::bytes().map(|b| b.len()).collect::<Vec<_>>(), [4, 0, 1, 0]);
+        assert_eq!(snailx::MappedArgs::os().map(|s| s.len()).collect::<Vec<_>>(), [4, 0, 1, 0]);
+        assert_eq!(snailx::Args::new().map(|a| a.len()).collect::<Vec<_>>(), [4, 0, 1, 0]);
+        assert!(snailx::MappedArgs::utf8_checked().all(|r| r.is_ok()));
+    });
+}
+
+// checked feature tests
+
+// argv with null entries before argc, or overridden with garbage, ends at the first null entry
+#[cfg(feature = "checked")]
+#[test]
+fn checked_null_entries() {
+    use core::ptr::null;
+
+    const ARGS: [*const u8; 4] = ["prog\0".as_ptr(), "a\0".as_ptr(), null(), "b\0".as_ptr()];
+    const LEADING: [*const u8; 2] = [null(), "a\0".as_ptr()];
+
+    for &argc in &[4, 5, u32::MAX] {
+        let _g = unsafe { ThreadArgvGuard::new(argc, ARGS.as_ptr()) };
+        assert_eq!(snailx::Args::new().len(), 2);
+        assert_eq!(snailx::MappedArgs::utf8().collect::<Vec<_>>(), ["prog", "a"]);
+        assert_eq!(snailx::MappedArgs::os().count(), 2);
+        #[cfg(feature = "rev_iter")]
+        assert_eq!(snailx::MappedArgs::bytes().rev().collect::<Vec<_>>(), [b"a", b"prog" as &[u8]]);
+        assert_eq!(snailx::prog_name_str(), Some("prog"));
+        assert_eq!(unsafe { snailx::Args::from_raw(argc, ARGS.as_ptr()) }.count(), 2);
+    }
+
+    for &(argc, argv) in &[(2, LEADING.as_ptr()), (3, null()), (0, null())] {
+        let _g = unsafe { ThreadArgvGuard::new(argc, argv) };
+        assert_eq!(snailx::Args::new().next(), None);
+        assert_eq!(snailx::MappedArgs::utf8().next(), None);
+        assert_eq!(snailx::MappedArgs::utf8_checked().len(), 0);
+        assert_eq!(snailx::prog_name(), None);
+        assert_eq!(unsafe { snailx::Args::from_raw(argc, argv) }.len(), 0);
+    }
+}
+
+#[cfg(all(feature = "checked", feature = "indexing_parser"))]
+#[test]
+fn checked_parser_without_args() {
+    use {
+        core::ptr::null,
+        snailx::indexing_parser::{Error, IndexingParser, OptRule}
+    };
+
+    let _g = unsafe { ThreadArgvGuard::new(3, null()) };
+
+    let mut parser = IndexingParser::new();
+    assert_eq!(parser.parse(&[], .., &[], |s| s == "prog", false), Ok(()));
+    assert_eq!(parser.prog_name(), None);
+
+    let mut parser = IndexingParser::new();
+    match parser.parse(
+        &[OptRule::new_auto("verbose").set_required(true)],
+        1..,
+        &[],
+        |_| true,
+        false
+    ) {
+        Err(Error::MissingRequired(missing)) => assert_eq!(missing, ["verbose"]),
+        r => panic!("expected missing required options, got {:?}", r)
+    }
+}
+
+// auxiliary vector tests
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_proc_auxv() -> Vec<(usize, usize)> {
+    const W: usize = core::mem::size_of::<usize>();
+
+    let raw = std::fs::read("/proc/self/auxv").unwrap();
+    raw.chunks_exact(2 * W)
+        .map(|c| {
+            let mut ty = [0; W];
+            let mut val = [0; W];
+            ty.copy_from_slice(&c[..W]);
+            val.copy_from_slice(&c[W..]);
+            (usize::from_ne_bytes(ty), usize::from_ne_bytes(val))
+        })
+        .take_while(|&(ty, _)| ty != snailx::direct::AT_NULL)
+        .collect()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn auxv_matches_proc() {
+    assert_eq!(snailx::direct::auxv().collect::<Vec<_>>(), read_proc_auxv());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn auxv_accessors() {
+    use snailx::direct::{AT_EXECFN, AT_PAGESZ, AT_RANDOM, AT_SECURE, auxv_get};
+
+    let proc = read_proc_auxv();
+    let find = |ty| proc.iter().find(|&&(t, _)| t == ty).map(|&(_, v)| v);
+
+    assert_eq!(snailx::direct::page_size(), find(AT_PAGESZ));
+    assert_eq!(auxv_get(AT_PAGESZ), find(AT_PAGESZ));
+    assert_eq!(snailx::direct::is_secure(), find(AT_SECURE) != Some(0));
+    assert_eq!(snailx::direct::random_bytes().map(|r| r.as_ptr() as usize), find(AT_RANDOM));
+    assert_eq!(snailx::direct::exec_path().map(|p| p.as_ptr() as usize), find(AT_EXECFN));
+    assert!(auxv_get(usize::MAX).is_none());
+}
+
+// process title tests
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn process_title() {
+    use snailx::direct::{ProcessTitleError, set_process_title};
+
+    fn cmdline() -> Vec<u8> {
+        let mut c = std::fs::read("/proc/self/cmdline").unwrap();
+        while c.last() == Some(&0) {
+            c.pop();
+        }
+        c
+    }
+
+    // SAFETY (for all calls here): the arguments are only read through owned copies, and no other
+    //  test reads the real arguments or environment strings without holding the lock
+    assert_eq!(unsafe { set_process_title("bad\0title") }, Err(ProcessTitleError::InteriorNul));
+
+    // hold the global lock so the arguments aren't overridden while they're compared
+    let _g = ArgvGuard::lock();
+    let args = snailx::MappedArgs::os().map(|s| s.to_owned()).collect::<Vec<_>>();
+    let path = std::env::var_os("PATH");
+
+    match unsafe { set_process_title("snailx: testing (1 job)") } {
+        Ok(()) => {
+            assert_eq!(cmdline(), b"snailx: testing (1 job)");
+            // the original arguments and environment were moved out of the way
+            assert_eq!(snailx::MappedArgs::os().map(|s| s.to_owned()).collect::<Vec<_>>(), args);
+            assert_eq!(std::env::var_os("PATH"), path);
+
+            let long = "ü".repeat(256 * 1024);
+            unsafe { set_process_title(&long) }.unwrap();
+            let c = cmdline();
+            assert!(!c.is_empty() && long.as_bytes().starts_with(&c));
+            assert!(std::str::from_utf8(&c).is_ok());
+
+            unsafe { set_process_title("short") }.unwrap();
+            assert_eq!(cmdline(), b"short");
+            assert_eq!(snailx::MappedArgs::os().map(|s| s.to_owned()).collect::<Vec<_>>(), args);
+        }
+        // only glibc runs the init hook
+        Err(e) => {
+            assert!(cfg!(not(all(target_os = "linux", target_env = "gnu"))));
+            assert_eq!(e, ProcessTitleError::Unavailable);
+        }
+    }
+}
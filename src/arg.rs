@@ -0,0 +1,243 @@
+// an argument which remembers its length, so conversions don't have to rescan it.
+
+import! {
+    {
+        cmp::{Ord, Ordering, PartialEq},
+        convert::From,
+        fmt::{Debug, Formatter, Result as FmtResult},
+        marker::PhantomData,
+        result::Result,
+        slice,
+        str::{self, Utf8Error}
+    }
+}
+
+use crate::{
+    CStr,
+    Utf8Chunks,
+    display::{EscapeDebug, LossyDisplay},
+    ffi::strlen
+};
+
+/// A null-terminated argument along with its length, as yielded by [`Args::sized`].
+///
+/// Unlike [`CStr`], which runs `strlen` for every length-dependent operation, the length is found
+/// once when the `Arg` is created, so [`len`](Arg::len), [`to_bytes`](Arg::to_bytes),
+/// [`to_stdlib`](Arg::to_stdlib) and [`to_osstr`](Arg::to_osstr) are all O(1) afterward.
+/// [`to_str`](Arg::to_str) still has to validate UTF-8, but skips the scan for the terminator.
+///
+/// Like `CStr`, the derived `PartialEq`, `PartialOrd` and `Hash` use the pointer and length, not
+/// the contents; compare with a `str` or `[u8]`, use [`content_eq`](Arg::content_eq), or wrap the
+/// `Arg` in [`ByContent`](crate::ByContent) for that.
+///
+/// [`Args::sized`]: crate::Args::sized
+///
+/// # Examples
+///
+/// ```
+/// let argv = snailx::argv!["prog", "--flag"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// let flag = args.sized().nth(1).unwrap();
+/// assert_eq!(flag.len(), 6);
+/// assert_eq!(flag.to_str(), Ok("--flag"));
+/// assert!(flag == "--flag");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Arg<'a> {
+    ptr: *const u8,
+    len: usize,
+    _marker: PhantomData<&'a [u8]>
+}
+
+#[allow(clippy::inline_always)]
+impl<'a> Arg<'a> {
+    /// Creates an `Arg` from a pointer to a null-terminated string, finding its length.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`CStr::from_ptr`].
+    #[must_use]
+    #[inline(always)]
+    pub unsafe fn from_ptr(p: *const u8) -> Arg<'a> {
+        assume!(!p.is_null(), "`from_ptr`: Arg pointer must be non-null");
+        Arg { ptr: p, len: strlen(p), _marker: PhantomData }
+    }
+
+    /// Creates an `Arg` from a pointer to a null-terminated string and its length, without
+    /// checking either.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`CStr::from_ptr`], and `p.add(len)` must be the null terminator.
+    #[must_use]
+    #[inline(always)]
+    pub const unsafe fn from_raw_parts(p: *const u8, len: usize) -> Arg<'a> {
+        Arg { ptr: p, len, _marker: PhantomData }
+    }
+
+    /// Gets a pointer to the start of this `Arg`.
+    #[must_use]
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Gets the length of this `Arg`, without the null terminator.
+    #[must_use]
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this `Arg` is empty.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets this `Arg` as a [`CStr`], which forgets the length.
+    #[must_use]
+    #[inline(always)]
+    pub fn as_cstr(&self) -> CStr<'a> {
+        // SAFETY: the pointer is to a valid, null-terminated string
+        unsafe { CStr::from_ptr(self.ptr) }
+    }
+
+    /// Gets the contents of this `Arg` as a byte slice, without the null terminator.
+    #[must_use]
+    #[inline(always)]
+    pub fn to_bytes(&self) -> &'a [u8] {
+        // SAFETY: the string is valid for `len` bytes and lives for 'a
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Gets the contents of this `Arg` as a byte slice, including the null terminator.
+    #[must_use]
+    #[inline(always)]
+    pub fn to_bytes_with_nul(&self) -> &'a [u8] {
+        // SAFETY: the string and its terminator are valid for `len + 1` bytes and live for 'a
+        unsafe { slice::from_raw_parts(self.ptr, self.len + 1) }
+    }
+
+    /// Gets the contents of this `Arg` as a `&str`, if they are valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Utf8Error`] describing the first invalid sequence if the contents are not
+    /// valid UTF-8.
+    #[inline]
+    pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+        str::from_utf8(self.to_bytes())
+    }
+
+    /// Returns an adaptor which displays this `Arg` as UTF-8, replacing invalid sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`, without allocating. See [`CStr::display`].
+    #[must_use]
+    #[inline]
+    pub fn display(&self) -> LossyDisplay<'a> {
+        LossyDisplay { bytes: self.to_bytes() }
+    }
+
+    /// Returns an adaptor which displays this `Arg` with control characters, quotes and invalid
+    /// UTF-8 escaped. See [`CStr::escape_debug`].
+    #[must_use]
+    #[inline]
+    pub fn escape_debug(&self) -> EscapeDebug<'a> {
+        EscapeDebug { bytes: self.to_bytes() }
+    }
+
+    /// Returns an iterator over the valid UTF-8 runs and invalid sequences in this `Arg`, without
+    /// allocating. See [`CStr::utf8_chunks`].
+    #[must_use]
+    #[inline]
+    pub fn utf8_chunks(&self) -> Utf8Chunks<'a> {
+        Utf8Chunks::new(self.to_bytes())
+    }
+
+    /// Returns whether this `Arg` has the same contents as `other`, unlike `==`, which compares
+    /// pointers.
+    #[must_use]
+    #[inline]
+    pub fn content_eq(&self, other: &Arg<'_>) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+
+    /// Compares the contents of this `Arg` and `other` bytewise, unlike `cmp`, which compares
+    /// pointers.
+    #[must_use]
+    #[inline]
+    pub fn content_cmp(&self, other: &Arg<'_>) -> Ordering {
+        self.to_bytes().cmp(other.to_bytes())
+    }
+
+    #[cfg(all(feature = "std", not(feature = "to_core_cstr")))]
+    /// Converts this value into the `std` equivalent, without rescanning it.
+    #[must_use]
+    #[inline(always)]
+    pub fn to_stdlib(&self) -> &'a ::std::ffi::CStr {
+        // SAFETY: the bytes end with the terminator, and there are no null bytes before it
+        unsafe { ::std::ffi::CStr::from_bytes_with_nul_unchecked(self.to_bytes_with_nul()) }
+    }
+
+    #[cfg(feature = "to_core_cstr")]
+    /// Converts this value into the `core` equivalent, without rescanning it.
+    #[must_use]
+    #[inline(always)]
+    pub fn to_stdlib(&self) -> &'a ::core::ffi::CStr {
+        // SAFETY: the bytes end with the terminator, and there are no null bytes before it
+        unsafe { ::core::ffi::CStr::from_bytes_with_nul_unchecked(self.to_bytes_with_nul()) }
+    }
+
+    #[cfg(feature = "std")]
+    /// Gets the contents of this `Arg` as an `OsStr`, without rescanning it.
+    #[must_use]
+    #[inline(always)]
+    pub fn to_osstr(&self) -> &'a ::std::ffi::OsStr {
+        use std::os::unix::ffi::OsStrExt;
+
+        ::std::ffi::OsStr::from_bytes(self.to_bytes())
+    }
+}
+
+/// Writes the contents in quotes, escaped like [`escape_debug`](Arg::escape_debug).
+impl Debug for Arg<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&self.escape_debug(), f)
+    }
+}
+
+impl<'a> From<CStr<'a>> for Arg<'a> {
+    #[inline]
+    fn from(c: CStr<'a>) -> Arg<'a> {
+        // SAFETY: `CStr`s always point to valid, null-terminated strings
+        unsafe { Arg::from_ptr(c.as_ptr()) }
+    }
+}
+
+impl PartialEq<[u8]> for Arg<'_> {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.to_bytes() == other
+    }
+}
+impl PartialEq<&[u8]> for Arg<'_> {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.to_bytes() == *other
+    }
+}
+impl PartialEq<str> for Arg<'_> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.to_bytes() == other.as_bytes()
+    }
+}
+impl PartialEq<&str> for Arg<'_> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.to_bytes() == other.as_bytes()
+    }
+}
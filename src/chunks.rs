@@ -0,0 +1,134 @@
+// splitting argument bytes into valid UTF-8 and invalid runs, without allocating.
+//  `core::str::Utf8Chunks` is above the msrv.
+
+import! {
+    {
+        fmt::{Debug, Formatter, Result as FmtResult},
+        iter::{FusedIterator, Iterator},
+        option::Option::{self, None, Some},
+        result::Result::{Err, Ok},
+        str
+    }
+}
+
+use crate::{
+    display::{EscapeDebug, LossyDisplay},
+    str_checks::validate_utf8
+};
+
+/// A run of valid UTF-8 and the invalid sequence after it, as yielded by [`Utf8Chunks`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Utf8Chunk<'a> {
+    valid: &'a str,
+    invalid: &'a [u8]
+}
+
+impl<'a> Utf8Chunk<'a> {
+    /// Gets the valid UTF-8 before the invalid sequence. May be empty.
+    #[must_use]
+    #[inline]
+    pub fn valid(&self) -> &'a str {
+        self.valid
+    }
+
+    /// Gets the invalid sequence, which is empty only for the last chunk, if the bytes end validly.
+    /// Each invalid sequence is what a lossy conversion replaces with a single `U+FFFD`.
+    #[must_use]
+    #[inline]
+    pub fn invalid(&self) -> &'a [u8] {
+        self.invalid
+    }
+}
+
+/// An iterator which splits bytes into [`Utf8Chunk`]s: runs of valid UTF-8, each followed by the
+/// invalid sequence after it. Created by [`MappedArgs::utf8_lossy`], [`CStr::utf8_chunks`] and
+/// [`Arg::utf8_chunks`].
+///
+/// Unlike skipping invalid arguments, this keeps every argument, so they can be rendered or
+/// compared without losing their positions.
+///
+/// [`MappedArgs::utf8_lossy`]: crate::MappedArgs::utf8_lossy
+/// [`CStr::utf8_chunks`]: crate::CStr::utf8_chunks
+/// [`Arg::utf8_chunks`]: crate::Arg::utf8_chunks
+///
+/// # Examples
+///
+/// ```
+/// let argv = snailx::argv!["prog", "caf\u{e9}"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// for chunks in args.map_utf8_lossy() {
+///     for chunk in chunks {
+///         assert!(chunk.invalid().is_empty());
+///     }
+/// }
+///
+/// let chunks = snailx::Utf8Chunks::new(b"ok\xFFdone");
+/// let valid: Vec<&str> = chunks.map(|c| c.valid()).collect();
+/// assert_eq!(valid, ["ok", "done"]);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Utf8Chunks<'a> {
+    bytes: &'a [u8]
+}
+
+impl<'a> Utf8Chunks<'a> {
+    /// Creates an iterator over the chunks of `bytes`.
+    #[must_use]
+    #[inline]
+    pub const fn new(bytes: &'a [u8]) -> Utf8Chunks<'a> {
+        Utf8Chunks { bytes }
+    }
+
+    /// Gets the bytes which haven't been yielded yet.
+    #[must_use]
+    #[inline]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns an adaptor which displays the remaining bytes with each invalid sequence replaced
+    /// by `U+FFFD REPLACEMENT CHARACTER`.
+    #[must_use]
+    #[inline]
+    pub fn display(&self) -> LossyDisplay<'a> {
+        LossyDisplay { bytes: self.bytes }
+    }
+}
+
+impl<'a> Iterator for Utf8Chunks<'a> {
+    type Item = Utf8Chunk<'a>;
+
+    fn next(&mut self) -> Option<Utf8Chunk<'a>> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let (valid, invalid, rest) = match validate_utf8(self.bytes) {
+            Ok(()) => (self.bytes, &[][..], &[][..]),
+            Err(e) => {
+                let (valid, rest) = self.bytes.split_at(e.valid_up_to);
+                // `None` means the bytes end in the middle of a sequence
+                let (invalid, rest) = match e.error_len {
+                    Some(n) => rest.split_at(n as usize),
+                    None => (rest, &[][..])
+                };
+                (valid, invalid, rest)
+            }
+        };
+        self.bytes = rest;
+        // SAFETY: `validate_utf8` just validated this prefix
+        Some(Utf8Chunk { valid: unsafe { str::from_utf8_unchecked(valid) }, invalid })
+    }
+}
+
+impl FusedIterator for Utf8Chunks<'_> {}
+
+/// Writes the remaining bytes in quotes, escaped like
+/// [`CStr::escape_debug`](crate::CStr::escape_debug).
+impl Debug for Utf8Chunks<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("Utf8Chunks").field(&EscapeDebug { bytes: self.bytes }).finish()
+    }
+}
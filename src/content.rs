@@ -0,0 +1,109 @@
+// content-based comparisons for the pointer-compared string types, so they can be used as keys.
+
+import! {
+    {
+        clone::Clone,
+        cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd},
+        convert::From,
+        fmt::{Debug, Formatter, Result as FmtResult},
+        hash::{Hash, Hasher},
+        marker::Copy,
+        ops::Deref,
+        option::Option::{self, Some}
+    }
+}
+
+use crate::{Arg, CStr};
+
+/// A wrapper which compares, orders and hashes [`CStr`]s and [`Arg`]s by their contents rather than
+/// their pointers.
+///
+/// `CStr` and `Arg` compare by address, so two arguments with the same text compare unequal, and
+/// using them as map keys keys on the address. Wrapping them in `ByContent` makes `==`, `<`, and
+/// `Hash` use the bytes instead (compared bytewise, like `[u8]`), so arguments can be deduplicated
+/// and sorted without converting them to standard library types.
+///
+/// # Examples
+///
+/// ```
+/// use {snailx::ByContent, std::collections::BTreeSet};
+///
+/// let argv = snailx::argv!["-b", "-a", "-b"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// let unique: BTreeSet<_> = args.map(ByContent).collect();
+/// let unique: Vec<&[u8]> = unique.iter().map(|a| a.to_bytes()).collect();
+/// assert_eq!(unique, [b"-a", b"-b"]);
+/// ```
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+pub struct ByContent<T>(pub T);
+
+impl<T> ByContent<T> {
+    /// Gets the wrapped value.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for ByContent<T> {
+    #[inline]
+    fn from(v: T) -> ByContent<T> {
+        ByContent(v)
+    }
+}
+
+impl<T> Deref for ByContent<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Debug> Debug for ByContent<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("ByContent").field(&self.0).finish()
+    }
+}
+
+macro_rules! by_content {
+    ($($ty:ident),*) => {
+        $(
+            impl PartialEq for ByContent<$ty<'_>> {
+                #[inline]
+                fn eq(&self, other: &Self) -> bool {
+                    self.0.content_eq(&other.0)
+                }
+            }
+            impl Eq for ByContent<$ty<'_>> {}
+
+            impl PartialOrd for ByContent<$ty<'_>> {
+                #[inline]
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+            impl Ord for ByContent<$ty<'_>> {
+                #[inline]
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.0.content_cmp(&other.0)
+                }
+            }
+
+            // hashes like `[u8]`, so `CStr`s and `Arg`s with the same contents hash the same
+            impl Hash for ByContent<$ty<'_>> {
+                #[inline]
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.0.to_bytes().hash(state);
+                }
+            }
+        )*
+    };
+}
+
+by_content!(CStr, Arg);
@@ -1,3 +1,10 @@
-#![doc = "Direct, platform-specific access to `argc` and `argv`. Most users should prefer the \
-          higher-level iterators in the crate root."]
+#![doc = "Direct, platform-specific access to `argc`, `argv`, `envp`, and (on Linux) the auxiliary \
+          vector. Most users should prefer the higher-level iterators in the crate root."]
 include! { concat!(env!("OUT_DIR"), "/direct.rs") }
+
+#[cfg(any(target_os = "linux", target_os = "android"))] mod auxvec;
+#[cfg(any(target_os = "linux", target_os = "android"))] pub(crate) mod procfs;
+mod seqlock;
+#[cfg(any(target_os = "linux", target_os = "android"))] mod title;
+
+#[cfg(any(target_os = "linux", target_os = "android"))] pub use self::{auxvec::*, title::*};
@@ -0,0 +1,186 @@
+// ELF auxiliary vector access. On glibc, the init hook finds it right after envp's terminator in
+//  the startup block, as long as envp still is that block; everywhere else, it's read once from
+//  /proc/self/auxv. that read goes through libc's `open`/`read`, as making the syscalls directly
+//  needs `asm!`, which is above the msrv.
+
+import! {
+    {
+        iter::{FusedIterator, Iterator},
+        mem::size_of,
+        option::Option::{self, None, Some},
+        slice
+    }
+}
+
+use {
+    super::{
+        imp,
+        procfs::{Once, StaticBuf, read_file}
+    },
+    crate::CStr
+};
+
+/// End of the auxiliary vector.
+pub const AT_NULL: usize = 0;
+/// Address of the program headers of the executable.
+pub const AT_PHDR: usize = 3;
+/// System page size.
+pub const AT_PAGESZ: usize = 6;
+/// Base address of the program interpreter.
+pub const AT_BASE: usize = 7;
+/// Entry address of the executable.
+pub const AT_ENTRY: usize = 9;
+/// Real user ID.
+pub const AT_UID: usize = 11;
+/// Effective user ID.
+pub const AT_EUID: usize = 12;
+/// Real group ID.
+pub const AT_GID: usize = 13;
+/// Effective group ID.
+pub const AT_EGID: usize = 14;
+/// Pointer to a null-terminated string identifying the hardware platform.
+pub const AT_PLATFORM: usize = 15;
+/// Architecture-dependent hardware capability bits.
+pub const AT_HWCAP: usize = 16;
+/// Frequency of `times()`.
+pub const AT_CLKTCK: usize = 17;
+/// Non-zero if the program was started in secure mode (setuid, setgid, or file capabilities).
+pub const AT_SECURE: usize = 23;
+/// Pointer to 16 random bytes provided by the kernel.
+pub const AT_RANDOM: usize = 25;
+/// Further architecture-dependent hardware capability bits.
+pub const AT_HWCAP2: usize = 26;
+/// Pointer to the null-terminated pathname used to execute the program.
+pub const AT_EXECFN: usize = 31;
+
+/// The maximum number of entries kept when the auxiliary vector is read from `/proc/self/auxv`.
+/// The kernel currently provides around 20-30.
+pub const AUXV_CAP: usize = 64;
+
+// +1 for the AT_NULL terminator, which the zeroed buffer always keeps
+static ONCE: Once = Once::new();
+static BUF: StaticBuf<[[usize; 2]; AUXV_CAP + 1]> = StaticBuf::new([[0; 2]; AUXV_CAP + 1]);
+
+#[cold]
+#[inline(never)]
+fn proc_auxv() -> *const usize {
+    ONCE.call_once(|| {
+        // SAFETY: `call_once` guarantees nothing else touches the buffer until we're done. the last
+        //  entry is never written, so the vector is always terminated.
+        unsafe {
+            let buf = slice::from_raw_parts_mut(
+                BUF.get().cast::<u8>(),
+                AUXV_CAP * 2 * size_of::<usize>()
+            );
+            let _ = read_file(b"/proc/self/auxv\0", buf);
+        }
+    });
+
+    BUF.get().cast()
+}
+
+/// Returns an iterator over the auxiliary vector as `(type, value)` pairs, such as
+/// <code>([AT_PAGESZ], 4096)</code>.
+///
+/// The vector is taken from the startup block when `snailx`'s init hook ran and received the
+/// startup `envp`. Otherwise (on other libcs, or when an earlier constructor had already moved the
+/// environment), it is read once from `/proc/self/auxv` into a static buffer, which uses libc's
+/// `open` and `read`. If neither is available, the iterator is empty.
+///
+/// # Examples
+///
+/// ```no_run
+/// use snailx::direct::{AT_PAGESZ, auxv};
+///
+/// let page_size = auxv().find(|&(ty, _)| ty == AT_PAGESZ).map(|(_, val)| val);
+/// assert!(page_size.is_some());
+/// ```
+#[must_use]
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub fn auxv() -> Auxv {
+    let p = imp::auxv();
+    Auxv { cur: if p.is_null() { proc_auxv() } else { p } }
+}
+
+/// Gets the value of the auxiliary vector entry with type `ty`, or `None` if there is no such
+/// entry.
+#[must_use]
+pub fn auxv_get(ty: usize) -> Option<usize> {
+    for (t, v) in auxv() {
+        if t == ty {
+            return Some(v);
+        }
+    }
+    None
+}
+
+/// Returns the pathname used to execute the program (`AT_EXECFN`).
+///
+/// Unlike `argv[0]`, which whoever executed the program can set freely, this is the path that was
+/// actually passed to `execve` (though it may still be relative).
+#[must_use]
+pub fn exec_path() -> Option<CStr<'static>> {
+    match auxv_get(AT_EXECFN) {
+        // SAFETY: the kernel guarantees this points to a null-terminated string that lives for
+        //  the whole program
+        Some(p) if p != 0 => Some(unsafe { CStr::from_ptr(p as *const u8) }),
+        _ => None
+    }
+}
+
+/// Returns whether the program was started in secure mode (`AT_SECURE`), i.e. it is setuid,
+/// setgid, or has file capabilities.
+///
+/// If the auxiliary vector is unavailable, this conservatively returns `true`, so callers relying
+/// on it to decide whether to trust their environment or arguments fail closed.
+#[must_use]
+#[allow(clippy::unnecessary_map_or)]
+pub fn is_secure() -> bool {
+    auxv_get(AT_SECURE).map_or(true, |v| v != 0)
+}
+
+/// Returns the 16 random bytes the kernel provides to every program (`AT_RANDOM`).
+///
+/// Note that libc may already use these (e.g. for stack protector canaries and pointer guards), so
+/// they shouldn't be used as key material directly.
+#[must_use]
+pub fn random_bytes() -> Option<&'static [u8; 16]> {
+    match auxv_get(AT_RANDOM) {
+        // SAFETY: the kernel guarantees this points to 16 bytes that live for the whole program
+        Some(p) if p != 0 => Some(unsafe { &*(p as *const [u8; 16]) }),
+        _ => None
+    }
+}
+
+/// Returns the system page size (`AT_PAGESZ`).
+#[must_use]
+pub fn page_size() -> Option<usize> {
+    auxv_get(AT_PAGESZ)
+}
+
+/// An iterator over the auxiliary vector as `(type, value)` pairs. See [`auxv`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Auxv {
+    cur: *const usize
+}
+
+impl Iterator for Auxv {
+    type Item = (usize, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<(usize, usize)> {
+        // SAFETY: `cur` points into a vector terminated by an AT_NULL entry we never advance past
+        unsafe {
+            let ty = self.cur.read();
+            if ty == AT_NULL {
+                return None;
+            }
+            let val = self.cur.add(1).read();
+            self.cur = self.cur.add(2);
+
+            Some((ty, val))
+        }
+    }
+}
+
+impl FusedIterator for Auxv {}
@@ -0,0 +1,182 @@
+//! Minimal helpers for reading small `/proc` files without `std` or allocation. Used as a fallback
+//! when the startup block isn't reachable through the init hook.
+//!
+//! Large buffers are mapped with [`map_anonymous`] when first needed rather than kept in static
+//! memory, so binaries that never take a fallback don't pay for them.
+
+import! {
+    {
+        cell::UnsafeCell,
+        marker::Sync,
+        ops::FnOnce,
+        option::Option::{self, None, Some},
+        iter::Iterator,
+        result::Result::{Err, Ok},
+        sync::atomic::{AtomicU8, Ordering}
+    }
+}
+// `hint::spin_loop` is above the msrv
+#[allow(deprecated)] use core::sync::atomic::spin_loop_hint;
+
+use crate::ffi::{
+    MAP_ANONYMOUS,
+    MAP_FAILED,
+    MAP_PRIVATE,
+    O_RDONLY,
+    PROT_READ,
+    PROT_WRITE,
+    close,
+    mmap,
+    open,
+    read,
+    readlink
+};
+
+/// A fixed-size buffer in static memory. Written exactly once, guarded by the caller's
+/// initialization state, and only read afterward.
+pub(crate) struct StaticBuf<T>(UnsafeCell<T>);
+
+// SAFETY: callers synchronize the single write with an atomic state before any reads
+unsafe impl<T> Sync for StaticBuf<T> {}
+
+impl<T> StaticBuf<T> {
+    pub(crate) const fn new(v: T) -> StaticBuf<T> {
+        StaticBuf(UnsafeCell::new(v))
+    }
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) const fn get(&self) -> *mut T {
+        self.0.get()
+    }
+}
+
+const UNINIT: u8 = 0;
+const BUSY: u8 = 1;
+const DONE: u8 = 2;
+
+/// A minimal `Once` for filling [`StaticBuf`]s lazily. Losers of the race spin until the winner is
+/// done, which is fine as the initializers only read a small file.
+pub(crate) struct Once(AtomicU8);
+
+impl Once {
+    pub(crate) const fn new() -> Once {
+        Once(AtomicU8::new(UNINIT))
+    }
+
+    /// Runs `f` if no other call has, otherwise waits for that call to finish. Everything `f`
+    /// wrote is visible once this returns.
+    #[allow(deprecated)]
+    pub(crate) fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.0.load(Ordering::Acquire) == DONE {
+            return;
+        }
+
+        match self.0.compare_exchange(UNINIT, BUSY, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                f();
+                self.0.store(DONE, Ordering::Release);
+            }
+            Err(_) => {
+                while self.0.load(Ordering::Acquire) != DONE {
+                    spin_loop_hint();
+                }
+            }
+        }
+    }
+}
+
+/// Maps `len` bytes of zeroed, private, read-write memory, or returns `None` if that failed. The
+/// mapping is never unmapped.
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub(crate) fn map_anonymous(len: usize) -> Option<*mut u8> {
+    // SAFETY: an anonymous mapping at an address of the kernel's choosing doesn't touch any
+    //  existing memory
+    let p = unsafe {
+        mmap(
+            switch!(core::ptr::null_mut()),
+            len,
+            PROT_READ | PROT_WRITE,
+            MAP_PRIVATE | MAP_ANONYMOUS,
+            -1,
+            0
+        )
+    };
+    if p == MAP_FAILED { None } else { Some(p) }
+}
+
+/// Splits `buf`, the contents of a file like `/proc/self/cmdline`, into its null-terminated
+/// strings, writing a pointer to each into `ptrs` and returning how many were written.
+///
+/// A trailing string without a null terminator was cut off by the buffer's capacity and is
+/// dropped, as are any strings past `ptrs.len()`.
+pub fn split_cmdline(buf: &[u8], ptrs: &mut [*const u8]) -> usize {
+    let mut n = 0;
+    let mut start = 0;
+    for (i, &b) in buf.iter().enumerate() {
+        if b == 0 {
+            if n == ptrs.len() {
+                break;
+            }
+            // SAFETY: `start` is at most `i`, which is in bounds
+            ptrs[n] = unsafe { buf.as_ptr().add(start) };
+            n += 1;
+            start = i + 1;
+        }
+    }
+    n
+}
+
+/// Reads the file at the null-terminated `path` into `buf`, returning the number of bytes read, or
+/// `None` if the file couldn't be opened. Reading stops once `buf` is full.
+///
+/// # Safety
+///
+/// `path` must be null-terminated.
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub(crate) unsafe fn read_file(path: &[u8], buf: &mut [u8]) -> Option<usize> {
+    assume!(
+        !path.is_empty() && path[path.len() - 1] == 0,
+        "`read_file`: path does not end with null byte"
+    );
+
+    let fd = open(path.as_ptr(), O_RDONLY);
+    if fd < 0 {
+        return None;
+    }
+
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = read(fd, buf.as_mut_ptr().add(filled), buf.len() - filled);
+        // 0 is eof, negative is an error. either way, keep what we have.
+        if n <= 0 {
+            break;
+        }
+        #[allow(clippy::cast_sign_loss)]
+        {
+            filled += n as usize;
+        }
+    }
+
+    close(fd);
+    Some(filled)
+}
+
+/// Reads the target of the symbolic link at the null-terminated `path` into `buf`, returning its
+/// length, or `None` if it couldn't be read or didn't fit. No null terminator is written.
+///
+/// # Safety
+///
+/// `path` must be null-terminated.
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub(crate) unsafe fn read_link(path: &[u8], buf: &mut [u8]) -> Option<usize> {
+    assume!(
+        !path.is_empty() && path[path.len() - 1] == 0,
+        "`read_link`: path does not end with null byte"
+    );
+
+    let n = readlink(path.as_ptr(), buf.as_mut_ptr(), buf.len());
+    // a full buffer may mean the target was truncated
+    #[allow(clippy::cast_sign_loss)]
+    if n < 0 || n as usize >= buf.len() { None } else { Some(n as usize) }
+}
@@ -0,0 +1,70 @@
+// a sequence lock for the `(argc, argv)` pair. the counter is odd while a write is in progress;
+//  writers serialize on it, and readers retry until they see the same even count before and after
+//  reading, so a reader never pairs one write's argc with another's argv.
+
+import! {
+    {
+        ops::{Fn, FnOnce},
+        result::Result::{Err, Ok},
+        sync::atomic::{AtomicUsize, Ordering, fence}
+    }
+}
+// `hint::spin_loop` is above the msrv
+#[allow(deprecated)] use core::sync::atomic::spin_loop_hint;
+
+pub(crate) struct SeqLock(AtomicUsize);
+
+impl SeqLock {
+    pub(crate) const fn new() -> SeqLock {
+        SeqLock(AtomicUsize::new(0))
+    }
+
+    /// Runs `f`, which must only perform relaxed loads of the guarded atomics, until it observes a
+    /// consistent snapshot, and returns it. Everything written before the snapshot's write is
+    /// visible afterward.
+    #[allow(clippy::inline_always, deprecated)]
+    #[inline(always)]
+    pub(crate) fn read<T, F: Fn() -> T>(&self, f: F) -> T {
+        loop {
+            let seq = self.0.load(Ordering::Acquire);
+            if seq & 1 == 0 {
+                let v = f();
+                fence(Ordering::Acquire);
+                if self.0.load(Ordering::Relaxed) == seq {
+                    return v;
+                }
+            }
+            spin_loop_hint();
+        }
+    }
+
+    /// Runs `f`, which may perform relaxed loads and stores of the guarded atomics, exclusively
+    /// with respect to other writers. Readers see either none or all of its stores.
+    #[allow(deprecated)]
+    pub(crate) fn write<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let mut seq = self.0.load(Ordering::Relaxed);
+        loop {
+            if seq & 1 == 0 {
+                // acquire to see the previous writer's stores
+                match self.0.compare_exchange_weak(
+                    seq,
+                    seq.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed
+                ) {
+                    Ok(_) => break,
+                    Err(s) => seq = s
+                }
+            } else {
+                spin_loop_hint();
+                seq = self.0.load(Ordering::Relaxed);
+            }
+        }
+        // keeps f's stores from being reordered before the odd count
+        fence(Ordering::Release);
+
+        let v = f();
+        self.0.store(seq.wrapping_add(2), Ordering::Release);
+        v
+    }
+}
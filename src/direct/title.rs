@@ -0,0 +1,214 @@
+// setproctitle-style rewriting of the process title. the kernel shows the argv string block in
+//  /proc/self/cmdline (and so `ps`/`top`), so the title is written over it, extended into the envp
+//  strings directly after it. the original strings are first copied into a mapped buffer so `Args`,
+//  `Env` and libc's `environ` keep seeing them.
+
+import! {
+    {
+        iter::Iterator,
+        mem::size_of,
+        option::Option::{None, Some},
+        ptr,
+        slice,
+        result::Result::{self, Err, Ok},
+        sync::atomic::{AtomicBool, Ordering}
+    }
+}
+// `hint::spin_loop` is above the msrv
+#[allow(deprecated)] use core::sync::atomic::spin_loop_hint;
+
+use {
+    super::{
+        imp,
+        procfs::{StaticBuf, map_anonymous}
+    },
+    crate::ffi::{environ, strlen}
+};
+
+/// The maximum number of bytes of argument and environment strings which can be moved out of the
+/// way by [`set_process_title`].
+pub const TITLE_STORE_CAP: usize = 128 * 1024;
+/// The maximum number of arguments which can be moved out of the way by [`set_process_title`].
+pub const TITLE_STORE_ARGS_CAP: usize = 4096;
+
+/// An error which can occur while setting the process title.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProcessTitleError {
+    /// The original argument memory is unknown because `snailx`'s init hook never ran (e.g. on
+    /// musl).
+    Unavailable,
+    /// The arguments don't fit in the backing store of [`TITLE_STORE_CAP`] bytes and
+    /// [`TITLE_STORE_ARGS_CAP`] arguments, or it couldn't be mapped.
+    TooLarge,
+    /// The title contains a null byte.
+    InteriorNul
+}
+
+static LOCK: AtomicBool = AtomicBool::new(false);
+// the writable region, as (start, len). a len of 0 means the arguments haven't been moved yet.
+static REGION: StaticBuf<(*mut u8, usize)> = StaticBuf::new((ptr::null_mut(), 0));
+
+/// Sets the process title shown by `ps`, `top` and `/proc/self/cmdline`, e.g.
+/// `worker: idle (3 jobs)`.
+///
+/// The title is written over the memory holding the original argument strings and any environment
+/// strings directly following them, and the rest of that memory is zeroed. Titles longer than that
+/// memory are truncated at a character boundary.
+///
+/// On the first call, the original strings are copied into a buffer mapped for them, and
+/// [`argc_argv`](crate::direct::argc_argv) and the environment's entries are pointed at the copies,
+/// so [`Args`] and [`Env`] created afterward still see the original values. Anything holding the
+/// original pointers (including `std::env::args` on some platforms) sees the title instead. If
+/// `argc`/`argv` were overridden with [`set_argc_argv`](crate::direct::set_argc_argv), the override
+/// is kept.
+///
+/// # Safety
+///
+/// This writes to memory that `snailx`'s safe APIs hand out `'static` references into. The caller
+/// must ensure that no reference into the original argument or environment strings is live or
+/// used again after the first call, on any thread. That includes every [`CStr`](crate::CStr),
+/// [`Arg`](crate::Arg), `&str`, `&[u8]` and `&OsStr` obtained before it from [`Args`],
+/// [`MappedArgs`](crate::MappedArgs), [`Env`], [`prog_name`](crate::prog_name) and the parsers, and
+/// every iterator over the arguments or environment created before it.
+/// Calling it first thing in `main`, before anything reads the arguments, is the easiest way to
+/// ensure this.
+///
+/// Environment entries are repointed with plain writes, so this must also not race with other
+/// threads reading the environment, e.g. through `getenv`.
+///
+/// # Errors
+///
+/// - [`ProcessTitleError::Unavailable`] if the original argument memory is unknown, which is the
+///   case unless `snailx`'s init hook ran (Linux with glibc).
+/// - [`ProcessTitleError::TooLarge`] if the original arguments don't fit in the backing store.
+/// - [`ProcessTitleError::InteriorNul`] if `title` contains a null byte.
+///
+/// [`Args`]: crate::Args
+/// [`Env`]: crate::Env
+///
+/// # Examples
+///
+/// ```no_run
+/// // SAFETY: nothing has read the arguments or environment yet
+/// unsafe { snailx::direct::set_process_title("worker: idle (3 jobs)") }.unwrap();
+/// ```
+#[cfg_attr(not(feature = "no_cold"), cold)]
+#[allow(deprecated)]
+pub unsafe fn set_process_title(title: &str) -> Result<(), ProcessTitleError> {
+    if title.as_bytes().contains(&0) {
+        return Err(ProcessTitleError::InteriorNul);
+    }
+
+    while LOCK.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        spin_loop_hint();
+    }
+    // SAFETY: the lock is held
+    let res = write_title(title);
+    LOCK.store(false, Ordering::Release);
+
+    res
+}
+
+// must be called with LOCK held
+unsafe fn write_title(title: &str) -> Result<(), ProcessTitleError> {
+    let region = &mut *REGION.get();
+    if region.1 == 0 {
+        *region = freeze()?;
+    }
+    let (start, len) = *region;
+
+    // keep the final byte as a terminator
+    let mut n = if title.len() < len { title.len() } else { len - 1 };
+    while !title.is_char_boundary(n) {
+        n -= 1;
+    }
+
+    ptr::copy_nonoverlapping(title.as_ptr(), start, n);
+    ptr::write_bytes(start.add(n), 0, len - n);
+
+    Ok(())
+}
+
+// copies the argument strings, and the environment strings directly following them, into a mapped
+//  buffer, repoints argv and the environment at the copies, and returns the region they used to
+//  occupy.
+unsafe fn freeze() -> Result<(*mut u8, usize), ProcessTitleError> {
+    let (argc, argv, envp) = imp::startup();
+    if argv.is_null() || argc == 0 || argv.read().is_null() {
+        return Err(ProcessTitleError::Unavailable);
+    }
+    let argc = argc as usize;
+    if argc > TITLE_STORE_ARGS_CAP {
+        return Err(ProcessTitleError::TooLarge);
+    }
+
+    // the region is the longest run of back-to-back strings starting at argv[0]. arguments outside
+    //  of it (which the kernel never produces) are left alone.
+    let start = argv.read();
+    let mut end = start;
+    let mut contiguous = true;
+    for i in 0..argc {
+        let p = argv.add(i).read();
+        if p != end {
+            contiguous = false;
+            break;
+        }
+        end = p.add(strlen(p) + 1);
+    }
+    if end as usize - start as usize > TITLE_STORE_CAP {
+        return Err(ProcessTitleError::TooLarge);
+    }
+
+    if contiguous && !envp.is_null() {
+        let mut e = envp;
+        while !e.read().is_null() && e.read() == end {
+            let next = end.add(strlen(end) + 1);
+            // out of space; the remaining strings simply stay where they are
+            if next as usize - start as usize > TITLE_STORE_CAP {
+                break;
+            }
+            end = next;
+            e = e.add(1);
+        }
+    }
+
+    let len = end as usize - start as usize;
+    // the pointers, followed by the strings they point into
+    let ptrs_len = TITLE_STORE_ARGS_CAP * size_of::<*const u8>();
+    let store = match map_anonymous(ptrs_len + TITLE_STORE_CAP) {
+        Some(p) => p,
+        None => return Err(ProcessTitleError::TooLarge)
+    };
+    let ptrs = slice::from_raw_parts_mut(store.cast::<*const u8>(), TITLE_STORE_ARGS_CAP);
+    let strs = store.add(ptrs_len);
+    ptr::copy_nonoverlapping(start, strs, len);
+
+    // maps a pointer into the region to its copy
+    let remap = |p: *const u8| -> *const u8 {
+        if p >= start && p < end { strs.add(p as usize - start as usize) } else { p }
+    };
+
+    for (i, slot) in ptrs.iter_mut().enumerate().take(argc) {
+        *slot = remap(argv.add(i).read());
+    }
+
+    // libc's environ is usually the startup envp, but moves once it has been grown by setenv. it's
+    //  read by value, which creates no reference to the `static mut`; `addr_of!` is above the
+    //  msrv.
+    for &env in &[envp, environ] {
+        if env.is_null() {
+            continue;
+        }
+        let mut e = env as *mut *const u8;
+        while !e.read().is_null() {
+            e.write(remap(e.read()));
+            e = e.add(1);
+        }
+    }
+
+    // the strings are about to be overwritten by the title, so argv is no longer one block
+    crate::iter::contiguous::invalidate();
+    imp::replace_argv(argv, ptrs.as_ptr());
+
+    Ok((start as *mut u8, len))
+}
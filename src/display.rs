@@ -0,0 +1,87 @@
+// allocation-free formatting for argument bytes which may not be valid UTF-8.
+
+import! {
+    {
+        clone::Clone,
+        fmt::{Debug, Display, Formatter, Result as FmtResult, Write},
+        iter::Iterator,
+        option::Option::{None, Some},
+        result::Result::Ok,
+        write
+    }
+}
+
+use crate::Utf8Chunks;
+
+/// A [`Display`] adaptor which writes bytes as UTF-8, replacing invalid sequences with
+/// `U+FFFD REPLACEMENT CHARACTER`, without allocating. Created by
+/// [`CStr::display`](crate::CStr::display) and [`Arg::display`](crate::Arg::display).
+///
+/// Formatting options such as width and fill are applied like for `str` when the bytes are valid
+/// UTF-8, and ignored otherwise.
+#[derive(Copy, Clone)]
+pub struct LossyDisplay<'a> {
+    pub(crate) bytes: &'a [u8]
+}
+
+/// A [`Display`] adaptor which writes bytes like `str::escape_debug`, escaping control characters
+/// and quotes, and writing invalid UTF-8 bytes as `\xNN`. Created by
+/// [`CStr::escape_debug`](crate::CStr::escape_debug) and
+/// [`Arg::escape_debug`](crate::Arg::escape_debug).
+///
+/// Surrounding quotes aren't written; the `Debug` implementations of `CStr` and `Arg` add them.
+#[derive(Copy, Clone)]
+pub struct EscapeDebug<'a> {
+    pub(crate) bytes: &'a [u8]
+}
+
+impl Display for LossyDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let chunks = Utf8Chunks::new(self.bytes);
+        match chunks.clone().next() {
+            None => return f.pad(""),
+            // only the last chunk has no invalid bytes, so this is the whole string
+            Some(chunk) if chunk.invalid().is_empty() => return f.pad(chunk.valid()),
+            Some(_) => {}
+        }
+
+        for chunk in chunks {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_char('\u{FFFD}')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for LossyDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(&EscapeDebug { bytes: self.bytes }, f)
+    }
+}
+
+impl Display for EscapeDebug<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for chunk in Utf8Chunks::new(self.bytes) {
+            for c in chunk.valid().chars() {
+                // `str`'s `Debug` leaves single quotes alone, so do the same
+                if c == '\'' {
+                    f.write_char(c)?;
+                } else {
+                    write!(f, "{}", c.escape_debug())?;
+                }
+            }
+            for b in chunk.invalid() {
+                write!(f, "\\x{:02x}", b)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Debug for EscapeDebug<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "\"{}\"", self)
+    }
+}
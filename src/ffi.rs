@@ -13,31 +13,132 @@ pub type c_int = i32;
 #[cfg(not(any(target_arch = "avr", target_arch = "msp430")))]
 pub type c_uint = u32;
 
+#[cfg(not(feature = "rust_strlen"))]
 extern "C" {
     /// Gets the length of a C-style string by finding the first `\0` byte after the given pointer.
     pub fn strlen(s: *const c_char) -> size_t;
 }
+#[cfg(feature = "rust_strlen")] pub use crate::strlen::strlen_bytes as strlen;
+
+// also used for reading response files
+#[cfg(any(target_os = "linux", target_os = "android", feature = "alloc"))]
+pub type ssize_t = isize;
+
+#[cfg(any(target_os = "linux", target_os = "android", feature = "alloc"))]
+pub const O_RDONLY: c_int = 0;
+
+#[cfg(any(target_os = "linux", target_os = "android", feature = "alloc"))]
+extern "C" {
+    /// Opens the file at the null-terminated `path`, returning a file descriptor or `-1`.
+    pub fn open(path: *const c_char, flags: c_int, ...) -> c_int;
+    /// Reads up to `count` bytes from `fd` into `buf`, returning the number read or `-1`.
+    pub fn read(fd: c_int, buf: *mut u8, count: size_t) -> ssize_t;
+    /// Closes the file descriptor `fd`.
+    pub fn close(fd: c_int) -> c_int;
+}
+
+// the same on every supported target
+#[cfg(feature = "alloc")]
+pub const EINTR: c_int = 4;
+
+#[cfg(feature = "alloc")]
+extern "C" {
+    /// Gets a pointer to the calling thread's `errno`.
+    #[cfg_attr(any(target_os = "linux", target_os = "emscripten"), link_name = "__errno_location")]
+    #[cfg_attr(
+        any(target_vendor = "apple", target_os = "freebsd", target_os = "dragonfly"),
+        link_name = "__error"
+    )]
+    #[cfg_attr(
+        any(target_os = "android", target_os = "netbsd", target_os = "openbsd"),
+        link_name = "__errno"
+    )]
+    #[cfg_attr(any(target_os = "solaris", target_os = "illumos"), link_name = "___errno")]
+    pub fn errno_location() -> *mut c_int;
+}
+
+// `long` on every Linux target
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub type off_t = isize;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PROT_READ: c_int = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PROT_WRITE: c_int = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MAP_PRIVATE: c_int = 2;
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(any(target_arch = "mips", target_arch = "mips64"))
+))]
+pub const MAP_ANONYMOUS: c_int = 0x20;
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(target_arch = "mips", target_arch = "mips64")
+))]
+pub const MAP_ANONYMOUS: c_int = 0x800;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MAP_FAILED: *mut u8 = !0 as *mut u8;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+extern "C" {
+    /// Reads the target of the symbolic link at the null-terminated `path` into `buf`, without a
+    /// null terminator, returning the number of bytes written or `-1`.
+    pub fn readlink(path: *const c_char, buf: *mut u8, bufsiz: size_t) -> ssize_t;
+
+    /// Maps `len` bytes of memory, returning [`MAP_FAILED`] on failure. Only used for anonymous,
+    /// private mappings here.
+    pub fn mmap(
+        addr: *mut u8,
+        len: size_t,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: off_t
+    ) -> *mut u8;
+
+    /// libc's current environment. libc reassigns it when the environment grows, e.g. in `setenv`.
+    pub static mut environ: *const *const c_char;
+}
 
 pub mod minimal_cstr {
     extern crate core;
 
-    use super::{c_char, strlen};
+    use {
+        super::{c_char, strlen},
+        crate::{
+            Utf8Chunks,
+            display::{EscapeDebug, LossyDisplay}
+        }
+    };
     import! {
-        {cmp::PartialEq, marker::PhantomData}
+        {
+            cmp::{Ord, Ordering, PartialEq},
+            fmt::{Debug, Formatter, Result as FmtResult},
+            iter::Iterator,
+            marker::PhantomData,
+            option::Option::{self, None, Some},
+            result::Result,
+            slice,
+            str::{self, Utf8Error}
+        }
     }
+    #[cfg(feature = "alloc")] use alloc::{borrow::Cow, string::String};
 
-    // TODO: make this a full-fledged CStr implementation so no need for to_stdlib
-
-    /// A minimal CStr implementation for use in place of `core::ffi::CStr` (unstable before 1.64)
-    /// and `std::ffi::CStr` (requires `std`).
+    /// A CStr implementation for use in place of `core::ffi::CStr` (unstable before 1.64) and
+    /// `std::ffi::CStr` (requires `std`).
+    ///
+    /// Everything works in `no_std`; [`to_stdlib`](CStr::to_stdlib) is only needed to pass the
+    /// string to APIs which take the standard library's type.
     ///
-    /// To do anything meaningful with this, you must convert it to the standard library's fully
-    /// implemented version via [`to_stdlib`](CStr::to_stdlib).
+    /// The length isn't stored, so [`len`](CStr::len), [`to_bytes`](CStr::to_bytes) and friends
+    /// run `strlen` on every call. Prefixes and comparisons ([`starts_with`](CStr::starts_with),
+    /// `==` with `str` or `[u8]`, etc.) stop at the first difference instead.
     ///
-    /// Do not call `to_stdlib` more than once, as every call runs `strlen` to determine the length
-    /// of the `CStr`.
+    /// Note that the derived `PartialEq`, `PartialOrd` and `Hash` compare pointers, not contents.
+    /// Use [`content_eq`](CStr::content_eq)/[`content_cmp`](CStr::content_cmp), or wrap the
+    /// `CStr` in [`ByContent`](crate::ByContent), to compare contents.
     #[repr(transparent)]
-    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct CStr<'a> {
         inner: *const c_char,
         _marker: PhantomData<&'a [c_char]>
@@ -65,20 +166,52 @@ pub mod minimal_cstr {
         }
     }
 
+    /// Writes the contents in quotes, escaped like [`escape_debug`](CStr::escape_debug).
+    impl Debug for CStr<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            Debug::fmt(&self.escape_debug(), f)
+        }
+    }
+
+    impl PartialEq<[u8]> for CStr<'_> {
+        #[inline]
+        fn eq(&self, other: &[u8]) -> bool {
+            self.strip_prefix(other).map_or(false, |rest| rest.is_empty())
+        }
+    }
+    impl PartialEq<&[u8]> for CStr<'_> {
+        #[inline]
+        fn eq(&self, other: &&[u8]) -> bool {
+            *self == **other
+        }
+    }
+    impl PartialEq<str> for CStr<'_> {
+        #[inline]
+        fn eq(&self, other: &str) -> bool {
+            *self == *other.as_bytes()
+        }
+    }
+    impl PartialEq<&str> for CStr<'_> {
+        #[inline]
+        fn eq(&self, other: &&str) -> bool {
+            *self == *other.as_bytes()
+        }
+    }
+
     #[allow(clippy::inline_always)]
-    #[allow(clippy::len_without_is_empty)]
     impl<'a> CStr<'a> {
         /// Gets a pointer to the start of this `CStr`.
         ///
         /// # Examples
         ///
         /// ```
-        /// // this is necessary for the test to not cause UB with miri; this shouldn't be necessary
-        /// //  in normal code.
-        /// const ARGS: [*const u8; 1] = ["argument\0".as_ptr()];
-        /// unsafe { snailx::direct::set_argc_argv(ARGS.len() as u32, ARGS.as_ptr()) };
+        /// // a fake argv is necessary for the test to not cause UB with miri; this shouldn't be
+        /// //  necessary in normal code.
+        /// let argv = snailx::argv!["argument"];
+        /// // SAFETY: `argv!` only produces static, null-terminated strings
+        /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
         ///
-        /// let first = snailx::Args::new().as_slice().get(0).copied();
+        /// let first = args.as_slice().get(0).copied();
         /// if let Some(c) = first {
         ///     let p = c.as_ptr();
         ///     assert!(!p.is_null());
@@ -92,19 +225,21 @@ pub mod minimal_cstr {
 
         /// Gets the length of this `CStr`.
         ///
-        /// Avoid calling this function more than once.
+        /// Avoid calling this function more than once; [`Arg`](crate::Arg) (from
+        /// [`Args::sized`](crate::Args::sized)) stores the length instead.
         ///
         /// # Examples
         ///
         /// ```
-        /// // this is necessary for the test to not cause UB with miri; this shouldn't be necessary
-        /// //  in normal code.
-        /// const ARGS: [*const u8; 1] = ["argument\0".as_ptr()];
-        /// unsafe { snailx::direct::set_argc_argv(ARGS.len() as u32, ARGS.as_ptr()) };
+        /// // a fake argv is necessary for the test to not cause UB with miri; this shouldn't be
+        /// //  necessary in normal code.
+        /// let argv = snailx::argv!["argument"];
+        /// // SAFETY: `argv!` only produces static, null-terminated strings
+        /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
         ///
-        /// let first = snailx::Args::new().as_slice().get(0).copied();
+        /// let first = args.as_slice().get(0).copied();
         /// if let Some(c) = first {
-        ///     let _l = c.len();
+        ///     assert_eq!(c.len(), 8);
         /// }
         /// ```
         #[must_use]
@@ -113,18 +248,304 @@ pub mod minimal_cstr {
             unsafe { strlen(self.inner) }
         }
 
+        /// Returns whether this `CStr` is empty. Unlike [`len`](CStr::len), this only reads the
+        /// first byte.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// assert!(snailx::cstr!("").is_empty());
+        /// assert!(!snailx::cstr!("a").is_empty());
+        /// ```
+        #[must_use]
+        #[inline(always)]
+        pub fn is_empty(&self) -> bool {
+            // SAFETY: the null terminator is always readable
+            unsafe { self.inner.read() == 0 }
+        }
+
+        /// Gets the contents of this `CStr` as a byte slice, without the null terminator.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// assert_eq!(snailx::cstr!("arg").to_bytes(), b"arg");
+        /// ```
+        #[must_use]
+        #[inline(always)]
+        pub fn to_bytes(&self) -> &'a [u8] {
+            // SAFETY: the string is valid for `len` bytes and lives for 'a
+            unsafe { slice::from_raw_parts(self.inner, self.len()) }
+        }
+
+        /// Gets the contents of this `CStr` as a byte slice, including the null terminator.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// assert_eq!(snailx::cstr!("arg").to_bytes_with_nul(), b"arg\0");
+        /// ```
+        #[must_use]
+        #[inline(always)]
+        pub fn to_bytes_with_nul(&self) -> &'a [u8] {
+            // SAFETY: the string and its terminator are valid for `len + 1` bytes and live for 'a
+            unsafe { slice::from_raw_parts(self.inner, self.len() + 1) }
+        }
+
+        /// Gets the contents of this `CStr` as a `&str`, if they are valid UTF-8.
+        ///
+        /// # Errors
+        ///
+        /// Returns the [`Utf8Error`] describing the first invalid sequence if the contents are not
+        /// valid UTF-8.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// assert_eq!(snailx::cstr!("arg").to_str(), Ok("arg"));
+        /// ```
+        #[inline]
+        pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+            str::from_utf8(self.to_bytes())
+        }
+
+        #[cfg(feature = "alloc")]
+        /// Gets the contents of this `CStr` as a `str`, replacing invalid UTF-8 sequences with
+        /// `U+FFFD REPLACEMENT CHARACTER`. Only allocates if there are invalid sequences.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let bytes = b"inv\xffalid\0";
+        /// // SAFETY: `bytes` ends with its only null byte
+        /// let c = unsafe { snailx::CStr::from_bytes_with_nul_unchecked(bytes) };
+        /// assert_eq!(c.to_string_lossy(), "inv\u{FFFD}alid");
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn to_string_lossy(&self) -> Cow<'a, str> {
+            String::from_utf8_lossy(self.to_bytes())
+        }
+
+        /// Returns an adaptor which displays this `CStr` as UTF-8, replacing invalid sequences with
+        /// `U+FFFD REPLACEMENT CHARACTER`, without allocating.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let bytes = b"inv\xffalid\0";
+        /// // SAFETY: `bytes` ends with its only null byte
+        /// let c = unsafe { snailx::CStr::from_bytes_with_nul_unchecked(bytes) };
+        /// assert_eq!(format!("{}", c.display()), "inv\u{FFFD}alid");
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn display(&self) -> LossyDisplay<'a> {
+            LossyDisplay { bytes: self.to_bytes() }
+        }
+
+        /// Returns an adaptor which displays this `CStr` like `str::escape_debug`, escaping control
+        /// characters and quotes, and showing invalid UTF-8 bytes as `\xNN`. This is also what
+        /// `CStr`'s `Debug` implementation writes, in quotes.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let bytes = b"tab\there\xff\0";
+        /// // SAFETY: `bytes` ends with its only null byte
+        /// let c = unsafe { snailx::CStr::from_bytes_with_nul_unchecked(bytes) };
+        /// assert_eq!(format!("{}", c.escape_debug()), "tab\\there\\xff");
+        /// assert_eq!(format!("{:?}", c), "\"tab\\there\\xff\"");
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn escape_debug(&self) -> EscapeDebug<'a> {
+            EscapeDebug { bytes: self.to_bytes() }
+        }
+
+        /// Returns an iterator over the valid UTF-8 runs and invalid sequences in this `CStr`,
+        /// without allocating.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let bytes = b"inv\xffalid\0";
+        /// // SAFETY: `bytes` ends with its only null byte
+        /// let c = unsafe { snailx::CStr::from_bytes_with_nul_unchecked(bytes) };
+        /// let chunks: Vec<_> = c.utf8_chunks().map(|c| (c.valid(), c.invalid())).collect();
+        /// assert_eq!(chunks, [("inv", &b"\xff"[..]), ("alid", &b""[..])]);
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn utf8_chunks(&self) -> Utf8Chunks<'a> {
+            Utf8Chunks::new(self.to_bytes())
+        }
+
+        /// Returns whether this `CStr` starts with `prefix`. Only reads as far as needed.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let c = snailx::cstr!("--name=value");
+        /// assert!(c.starts_with(b"--"));
+        /// assert!(!c.starts_with(b"--name=value-and-more"));
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn starts_with(&self, prefix: &[u8]) -> bool {
+            self.strip_prefix(prefix).is_some()
+        }
+
+        /// Returns the rest of this `CStr` after `prefix`, or `None` if it doesn't start with
+        /// `prefix`. Only reads as far as needed.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let c = snailx::cstr!("--name=value");
+        /// assert_eq!(c.strip_prefix(b"--").unwrap(), "name=value");
+        /// assert!(c.strip_prefix(b"-n").is_none());
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn strip_prefix(&self, prefix: &[u8]) -> Option<CStr<'a>> {
+            for (i, &b) in prefix.iter().enumerate() {
+                // SAFETY: every byte up to and including the terminator is readable, and the loop
+                //  stops at the terminator, since it never equals a byte of a matching prefix
+                let c = unsafe { self.inner.add(i).read() };
+                if c == 0 || c != b {
+                    return None;
+                }
+            }
+            // SAFETY: the whole prefix matched, so the terminator is at or after `prefix.len()`
+            Some(CStr { inner: unsafe { self.inner.add(prefix.len()) }, _marker: PhantomData })
+        }
+
+        /// Splits this `CStr` at the first occurrence of `delim`, returning the bytes before it
+        /// and the rest after it, or `None` if it doesn't contain `delim`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let c = snailx::cstr!("--name=value=more");
+        /// let (k, v) = c.split_once(b'=').unwrap();
+        /// assert_eq!(k, b"--name");
+        /// assert_eq!(v, "value=more");
+        /// assert!(c.split_once(b':').is_none());
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn split_once(&self, delim: u8) -> Option<(&'a [u8], CStr<'a>)> {
+            if delim == 0 {
+                return None;
+            }
+            let mut i = 0;
+            loop {
+                // SAFETY: the loop stops at the terminator
+                let c = unsafe { self.inner.add(i).read() };
+                if c == delim {
+                    // SAFETY: `i` bytes were just read, and the rest starts before the terminator
+                    return unsafe {
+                        Some((
+                            slice::from_raw_parts(self.inner, i),
+                            CStr { inner: self.inner.add(i + 1), _marker: PhantomData }
+                        ))
+                    };
+                } else if c == 0 {
+                    return None;
+                }
+                i += 1;
+            }
+        }
+
+        /// Returns whether this `CStr` equals `other`, ignoring ASCII case. Only reads as far as
+        /// needed.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// assert!(snailx::cstr!("--Help").eq_ignore_ascii_case(b"--HELP"));
+        /// assert!(!snailx::cstr!("--help").eq_ignore_ascii_case(b"--helpme"));
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn eq_ignore_ascii_case(&self, other: &[u8]) -> bool {
+            for (i, &b) in other.iter().enumerate() {
+                // SAFETY: as in `strip_prefix`
+                let c = unsafe { self.inner.add(i).read() };
+                if c == 0 || !c.eq_ignore_ascii_case(&b) {
+                    return false;
+                }
+            }
+            // SAFETY: as in `strip_prefix`
+            unsafe { self.inner.add(other.len()).read() == 0 }
+        }
+
+        /// Returns whether this `CStr` has the same contents as `other`, unlike `==`, which
+        /// compares pointers. Only reads as far as the first difference.
+        ///
+        /// See [`ByContent`](crate::ByContent) for using content comparisons in collections.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let a = snailx::cstr!("--flag");
+        /// let b = unsafe { snailx::CStr::from_bytes_with_nul_unchecked(b"--flag\0") };
+        /// assert!(a.content_eq(&b));
+        /// assert!(!a.content_eq(&snailx::cstr!("--flags")));
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn content_eq(&self, other: &CStr<'_>) -> bool {
+            self.content_cmp(other) == Ordering::Equal
+        }
+
+        /// Compares the contents of this `CStr` and `other` bytewise, like comparing their
+        /// [`to_bytes`](CStr::to_bytes), unlike `cmp`, which compares pointers. Only reads as far
+        /// as the first difference.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use std::cmp::Ordering;
+        ///
+        /// let a = snailx::cstr!("--alpha");
+        /// assert_eq!(a.content_cmp(&snailx::cstr!("--beta")), Ordering::Less);
+        /// assert_eq!(a.content_cmp(&snailx::cstr!("--al")), Ordering::Greater);
+        /// ```
+        #[must_use]
+        #[inline]
+        pub fn content_cmp(&self, other: &CStr<'_>) -> Ordering {
+            if self.inner == other.inner {
+                return Ordering::Equal;
+            }
+            let mut i = 0;
+            loop {
+                // SAFETY: both loops stop at the first terminator, which compares less than any
+                //  other byte, like the end of a slice
+                let (a, b) = unsafe { (self.inner.add(i).read(), other.inner.add(i).read()) };
+                if a != b {
+                    return a.cmp(&b);
+                } else if a == 0 {
+                    return Ordering::Equal;
+                }
+                i += 1;
+            }
+        }
+
         #[cfg(all(feature = "std", not(feature = "to_core_cstr")))]
         /// Converts this value into the `std` equivalent.
         ///
         /// # Examples
         ///
         /// ```
-        /// // this is necessary for the test to not cause UB with miri; this shouldn't be necessary
-        /// //  in normal code.
-        /// const ARGS: [*const u8; 1] = ["argument\0".as_ptr()];
-        /// unsafe { snailx::direct::set_argc_argv(ARGS.len() as u32, ARGS.as_ptr()) };
+        /// // a fake argv is necessary for the test to not cause UB with miri; this shouldn't be
+        /// //  necessary in normal code.
+        /// let argv = snailx::argv!["argument"];
+        /// // SAFETY: `argv!` only produces static, null-terminated strings
+        /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
         ///
-        /// let first = snailx::Args::new().as_slice().get(0).copied();
+        /// let first = args.as_slice().get(0).copied();
         /// if let Some(c) = first {
         ///     let s = c.to_stdlib().to_string_lossy();
         ///     let _ = s.len();
@@ -153,12 +574,13 @@ pub mod minimal_cstr {
         /// # Examples
         ///
         /// ```
-        /// // this is necessary for the test to not cause UB with miri; this shouldn't be necessary
-        /// //  in normal code.
-        /// const ARGS: [*const u8; 1] = ["argument\0".as_ptr()];
-        /// unsafe { snailx::direct::set_argc_argv(ARGS.len() as u32, ARGS.as_ptr()) };
+        /// // a fake argv is necessary for the test to not cause UB with miri; this shouldn't be
+        /// //  necessary in normal code.
+        /// let argv = snailx::argv!["argument"];
+        /// // SAFETY: `argv!` only produces static, null-terminated strings
+        /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
         ///
-        /// let first = snailx::Args::new().as_slice().get(0).copied();
+        /// let first = args.as_slice().get(0).copied();
         /// if let Some(c) = first {
         ///     let core_cstr = c.to_stdlib();
         ///     // Access the raw bytes (without the terminating nul):
@@ -187,12 +609,13 @@ pub mod minimal_cstr {
         /// # Examples
         ///
         /// ```
-        /// // this is necessary for the test to not cause UB with miri; this shouldn't be necessary
-        /// //  in normal code.
-        /// const ARGS: [*const u8; 1] = ["argument\0".as_ptr()];
-        /// unsafe { snailx::direct::set_argc_argv(ARGS.len() as u32, ARGS.as_ptr()) };
+        /// // a fake argv is necessary for the test to not cause UB with miri; this shouldn't be
+        /// //  necessary in normal code.
+        /// let argv = snailx::argv!["argument"];
+        /// // SAFETY: `argv!` only produces static, null-terminated strings
+        /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
         ///
-        /// let first = snailx::Args::new().as_slice().get(0).copied();
+        /// let first = args.as_slice().get(0).copied();
         /// if let Some(c) = first {
         ///     let p = c.as_ptr();
         ///     // SAFETY: Using a pointer provided by the OS for argv is valid here.
@@ -231,6 +654,19 @@ pub mod minimal_cstr {
 
             CStr { inner: p, _marker: PhantomData }
         }
+
+        /// Creates a `CStr` from a byte slice ending with a null terminator, without checking it.
+        ///
+        /// Prefer [`cstr!`](crate::cstr) for literals, which checks this at compile time.
+        ///
+        /// # Safety
+        ///
+        /// `bytes` must end with a null byte and contain no other null bytes.
+        #[must_use]
+        #[inline(always)]
+        pub const unsafe fn from_bytes_with_nul_unchecked(bytes: &'a [u8]) -> CStr<'a> {
+            CStr { inner: bytes.as_ptr().cast(), _marker: PhantomData }
+        }
     }
 
     #[cfg(all(feature = "std", not(feature = "to_core_cstr")))]
@@ -15,10 +15,14 @@ import! {
     iter::DoubleEndedIterator
 }
 
-use crate::{CStr, MappedArgs, direct, helpers, iter::len};
+use crate::{CStr, Fallible, Infallible, MappedArgs, Utf8Fallibility, direct, helpers, iter::len};
 
 // not Copy because that nets a 2-5% performance improvement for some reason
 /// An iterator over program arguments as <code>[CStr](CStr)<'static></code>.
+///
+/// Constructors take a single, consistent snapshot of `(argc, argv)` (see
+/// [`direct::argc_argv`]), so a concurrent [`direct::set_argc_argv`] never leaves an iterator with
+/// a mismatched length, and later calls don't affect existing iterators.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Args {
     pub(crate) cur: *const *const u8,
@@ -41,6 +45,17 @@ impl Args {
         Args { cur: argv, end: helpers::back(argv, argc) }
     }
 
+    /// Creates an `Args` instance over an explicit `argc` and `argv` rather than the program's.
+    ///
+    /// # Safety
+    ///
+    /// `argv` must point to at least `argc` valid pointers to null-terminated strings, all of which
+    /// must live for `'static`.
+    #[must_use]
+    pub unsafe fn from_raw(argc: u32, argv: *const *const u8) -> Args {
+        Args { cur: argv, end: helpers::back(argv, argc) }
+    }
+
     /// Gets the remaining arguments in this iterator as a slice.
     #[must_use]
     pub fn as_slice(&self) -> &'static [CStr<'static>] {
@@ -62,54 +77,128 @@ impl Args {
             end: self.end,
             map,
             // assume fallible for safety
-            #[cfg(feature = "infallible_map")]
-            fallible: true
+            fallibility: Fallible
         }
     }
 
+    /// Map this iterator to a different type. Like [`MappedArgs::new_exact`], but operates on an
+    /// existing iterator.
+    ///
+    /// # Safety
+    ///
+    /// `map` must never return `None`.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub unsafe fn map_ty_exact<Ret, F: Fn(*const u8) -> Option<Ret>>(
+        &self,
+        map: F
+    ) -> MappedArgs<Ret, F, Infallible> {
+        MappedArgs { cur: self.cur, end: self.end, map, fallibility: Infallible }
+    }
+
     #[cfg(feature = "infallible_map")]
-    /// Map this iterator to a different type. Like [`MappedArgs::new_infallible`], but operates on
-    /// an existing iterator.
+    /// Map this iterator to a different type. Like [`map_ty`](Args::map_ty), which it now is:
+    /// infallibility is part of [`MappedArgs`]'s type, so this can't mark the map infallible
+    /// without being `unsafe`.
+    #[deprecated(note = "use `map_ty`, or the unsafe `map_ty_exact` for an infallible map")]
     #[must_use]
     #[cfg_attr(not(feature = "no_cold"), cold)]
     pub fn map_ty_infallible<Ret, F: Fn(*const u8) -> Option<Ret>>(
         &self,
         map: F
     ) -> MappedArgs<Ret, F> {
-        MappedArgs { cur: self.cur, end: self.end, map, fallible: false }
+        self.map_ty(map)
     }
 
     #[cfg(any(feature = "std", feature = "to_core_cstr"))]
     /// Map this iterator to a different type. Like [`MappedArgs::stdlib_cstr`], but operates on an
     /// existing iterator.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
     pub fn map_stdlib_cstr(
         &self
-    ) -> MappedArgs<&'static crate::StdCStr, fn(*const u8) -> Option<&'static crate::StdCStr>> {
+    ) -> MappedArgs<
+        &'static crate::StdCStr,
+        fn(*const u8) -> Option<&'static crate::StdCStr>,
+        Infallible
+    > {
         MappedArgs {
             cur: self.cur,
             end: self.end,
             map: helpers::to_stdcstr,
-            #[cfg(feature = "infallible_map")]
-            fallible: false
+            fallibility: Infallible
         }
     }
 
     /// Map this iterator to `&'static str`. Like [`MappedArgs::utf8`], but operates on an existing
     /// iterator. Non-UTF-8 arguments are skipped.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
-    pub fn map_utf8(&self) -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>> {
+    pub fn map_utf8(
+        &self
+    ) -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>, Utf8Fallibility> {
         MappedArgs {
             cur: self.cur,
             end: self.end,
             map: helpers::try_to_str,
-            #[cfg(all(feature = "infallible_map", not(feature = "assume_valid_str")))]
-            fallible: true,
-            // assume_valid_str makes the map "infallible"
-            #[cfg(all(feature = "infallible_map", feature = "assume_valid_str"))]
-            fallible: false
+            #[cfg(not(feature = "assume_valid_str"))]
+            fallibility: Fallible,
+            // assume_valid_str makes the map infallible
+            #[cfg(feature = "assume_valid_str")]
+            fallibility: Infallible
+        }
+    }
+
+    /// Map this iterator to `&'static str` without checking that the arguments are valid UTF-8.
+    /// Like [`MappedArgs::utf8_unchecked`], but operates on an existing iterator.
+    ///
+    /// # Safety
+    ///
+    /// Every remaining argument must be valid UTF-8.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    #[allow(clippy::type_complexity)]
+    pub unsafe fn map_utf8_unchecked(
+        &self
+    ) -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>, Infallible> {
+        MappedArgs {
+            cur: self.cur,
+            end: self.end,
+            map: helpers::to_str_unchecked,
+            fallibility: Infallible
+        }
+    }
+
+    /// Map this iterator to `&'static [u8]`, without the null terminators. Like
+    /// [`MappedArgs::bytes`], but operates on an existing iterator.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn map_bytes(
+        &self
+    ) -> MappedArgs<&'static [u8], fn(*const u8) -> Option<&'static [u8]>, Infallible> {
+        MappedArgs { cur: self.cur, end: self.end, map: helpers::to_bytes, fallibility: Infallible }
+    }
+
+    /// Map this iterator to [`Utf8Chunks`](crate::Utf8Chunks). Like [`MappedArgs::utf8_lossy`], but
+    /// operates on an existing iterator.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn map_utf8_lossy(
+        &self
+    ) -> MappedArgs<
+        crate::Utf8Chunks<'static>,
+        fn(*const u8) -> Option<crate::Utf8Chunks<'static>>,
+        Infallible
+    > {
+        MappedArgs {
+            cur: self.cur,
+            end: self.end,
+            map: helpers::to_utf8_chunks,
+            fallibility: Infallible
         }
     }
 
@@ -117,18 +206,57 @@ impl Args {
     /// Map this iterator to `&'static OsStr`. Like [`MappedArgs::osstr`], but operates on an
     /// existing iterator.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     #[allow(unused_qualifications)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
     pub fn map_os(
         &self
-    ) -> MappedArgs<&'static ::std::ffi::OsStr, fn(*const u8) -> Option<&'static ::std::ffi::OsStr>>
-    {
-        MappedArgs {
-            cur: self.cur,
-            end: self.end,
-            map: helpers::to_osstr,
-            #[cfg(feature = "infallible_map")]
-            fallible: false
+    ) -> MappedArgs<
+        &'static ::std::ffi::OsStr,
+        fn(*const u8) -> Option<&'static ::std::ffi::OsStr>,
+        Infallible
+    > {
+        MappedArgs { cur: self.cur, end: self.end, map: helpers::to_osstr, fallibility: Infallible }
+    }
+
+    /// Returns a view of this iterator without its first argument, which is the program name for
+    /// a fresh iterator. Empty if there are no arguments.
+    #[must_use]
+    #[inline]
+    pub fn without_prog(&self) -> Args {
+        // SAFETY: cur is only advanced if it is in bounds
+        Args {
+            cur: if self.cur == self.end { self.cur } else { unsafe { self.cur.add(1) } },
+            end: self.end
+        }
+    }
+
+    /// Splits this iterator at its first `--` argument into the arguments before it and, if there
+    /// was one, the arguments after it. The `--` itself is in neither. Both share this iterator's
+    /// pointers, so nothing is copied or allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let argv = snailx::argv!["prog", "-v", "--", "run", "--", "x"];
+    /// // SAFETY: `argv!` only produces static, null-terminated strings
+    /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+    ///
+    /// let (opts, rest) = args.without_prog().split_at_double_dash();
+    /// assert_eq!(opts.map_utf8().collect::<Vec<_>>(), ["-v"]);
+    /// assert_eq!(rest.unwrap().map_utf8().collect::<Vec<_>>(), ["run", "--", "x"]);
+    /// ```
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn split_at_double_dash(&self) -> (Args, Option<Args>) {
+        // SAFETY: the pointers are from argv
+        match unsafe { crate::iter::find_double_dash(self.cur, self.end) } {
+            // SAFETY: `dd` is in bounds, so one past it is at most `end`
+            Some(dd) => (
+                Args { cur: self.cur, end: dd },
+                Some(Args { cur: unsafe { dd.add(1) }, end: self.end })
+            ),
+            None => (Args { cur: self.cur, end: self.end }, None)
         }
     }
 
@@ -0,0 +1,291 @@
+import! {
+    {
+        fmt::{Display, Formatter, Result as FmtResult},
+        iter::{ExactSizeIterator, FusedIterator, Iterator},
+        ops::FnMut,
+        option::Option::{self, None, Some},
+        result::Result::{self, Err, Ok},
+        slice,
+        str,
+        write
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+import! {
+    iter::DoubleEndedIterator
+}
+
+use crate::{
+    Args,
+    CStr,
+    MappedArgs,
+    iter::{
+        contiguous::{self, Block},
+        len
+    },
+    str_checks::validate_utf8
+};
+
+/// An argument which isn't valid UTF-8, as yielded by [`Utf8CheckedArgs`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidArg {
+    index: usize,
+    valid_up_to: usize,
+    error_len: Option<u8>,
+    arg: CStr<'static>
+}
+
+impl InvalidArg {
+    /// Gets the index of the argument in argv, counting the program name.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the number of bytes at the start of the argument which are valid UTF-8, like
+    /// [`Utf8Error::valid_up_to`](core::str::Utf8Error::valid_up_to).
+    #[must_use]
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Gets the length of the invalid sequence after [`valid_up_to`](InvalidArg::valid_up_to), or
+    /// `None` if the argument ends in the middle of a sequence, like
+    /// [`Utf8Error::error_len`](core::str::Utf8Error::error_len).
+    #[must_use]
+    pub fn error_len(&self) -> Option<usize> {
+        self.error_len.map(|n| n as usize)
+    }
+
+    /// Gets the argument itself.
+    #[must_use]
+    pub fn arg(&self) -> CStr<'static> {
+        self.arg
+    }
+}
+
+impl Display for InvalidArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "argument {} ({:?}) is not valid UTF-8 after byte {}",
+            self.index, self.arg, self.valid_up_to
+        )
+    }
+}
+
+/// An iterator over program arguments as `Result<&'static str, InvalidArg>`, which reports
+/// arguments that aren't valid UTF-8 rather than skipping them. Created by
+/// [`MappedArgs::utf8_checked`] and [`Args::map_utf8_checked`].
+///
+/// Arguments are validated even with the `assume_valid_str` feature.
+///
+/// # Examples
+///
+/// ```
+/// let argv = snailx::argv!["prog", "ok"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// let args: Result<Vec<&str>, snailx::InvalidArg> = args.map_utf8_checked().collect();
+/// assert_eq!(args.unwrap(), ["prog", "ok"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Utf8CheckedArgs {
+    inner: Args,
+    // the slot of index 0
+    base: *const *const u8,
+    // a block, already known to be valid UTF-8, which has every argument
+    block: Option<Block>
+}
+
+impl MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>> {
+    /// Returns an iterator over the program's arguments as `Result<&'static str, InvalidArg>`.
+    /// Unlike [`utf8`](MappedArgs::utf8), non-UTF-8 arguments are yielded as errors carrying their
+    /// index and the position of the invalid sequence.
+    ///
+    /// If the kernel laid the arguments out back-to-back (as Linux does), they are all validated
+    /// at once, the first time, rather than one at a time as they are yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// for arg in snailx::MappedArgs::utf8_checked() {
+    ///     match arg {
+    ///         Ok(arg) => println!("{}", arg),
+    ///         Err(e) => eprintln!("warning: {}", e)
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn utf8_checked() -> Utf8CheckedArgs {
+        Args::new().map_utf8_checked()
+    }
+}
+
+impl Args {
+    /// Map this iterator to `Result<&'static str, InvalidArg>`. Like
+    /// [`MappedArgs::utf8_checked`], but operates on an existing iterator. Indices are still
+    /// positions in argv if its arguments are the program's own, and otherwise count from its first
+    /// remaining argument.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn map_utf8_checked(&self) -> Utf8CheckedArgs {
+        let block = contiguous::covering(self.cur, self.end).filter(Block::is_utf8);
+        Utf8CheckedArgs::with_block(Args { cur: self.cur, end: self.end }, block)
+    }
+}
+
+impl Utf8CheckedArgs {
+    #[inline]
+    pub(crate) fn with_block(inner: Args, block: Option<Block>) -> Utf8CheckedArgs {
+        Utf8CheckedArgs { base: crate::iter::argv_base(inner.cur), inner, block }
+    }
+
+    // SAFETY: `slot` must be in `inner.cur..inner.end`
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    unsafe fn at(&self, slot: *const *const u8) -> Result<&'static str, InvalidArg> {
+        // the arguments were validated with the block, so only the length is needed. the block may
+        //  have been overwritten since this was created
+        let known = match self.block {
+            Some(b) if b.live() => b.len_at(slot),
+            _ => None
+        };
+        if let Some(n) = known {
+            // SAFETY: `len_at` checked that the argument is a whole string in the block, which is
+            //  valid UTF-8, so it starts and ends on character boundaries
+            return Ok(str::from_utf8_unchecked(slice::from_raw_parts(slot.read(), n)));
+        }
+
+        let arg = CStr::from_ptr(slot.read());
+        let bytes = arg.to_bytes();
+
+        match validate_utf8(bytes) {
+            // SAFETY: just validated
+            Ok(()) => Ok(str::from_utf8_unchecked(bytes)),
+            Err(e) => Err(InvalidArg {
+                index: len(self.base, slot),
+                valid_up_to: e.valid_up_to,
+                error_len: e.error_len,
+                arg
+            })
+        }
+    }
+}
+
+impl Iterator for Utf8CheckedArgs {
+    type Item = Result<&'static str, InvalidArg>;
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<Result<&'static str, InvalidArg>> {
+        if self.inner.cur == self.inner.end {
+            return None;
+        }
+        let slot = self.inner.cur;
+        // SAFETY: just checked that `slot` is in bounds
+        self.inner.cur = unsafe { slot.add(1) };
+        Some(unsafe { self.at(slot) })
+    }
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn last(self) -> Option<Result<&'static str, InvalidArg>> {
+        if self.inner.cur == self.inner.end {
+            return None;
+        }
+        // SAFETY: the iterator isn't empty, so the last slot is in bounds
+        Some(unsafe { self.at(self.inner.end.sub(1)) })
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Result<&'static str, InvalidArg>> {
+        if n >= self.inner.len() {
+            self.inner.cur = self.inner.end;
+            return None;
+        }
+        // SAFETY: just checked that `cur + n` is in bounds
+        self.inner.cur = unsafe { self.inner.cur.add(n) };
+        self.next()
+    }
+
+    #[inline]
+    fn fold<B, F: FnMut(B, Result<&'static str, InvalidArg>) -> B>(
+        self,
+        mut acc: B,
+        mut f: F
+    ) -> B {
+        let mut slot = self.inner.cur;
+        while slot != self.inner.end {
+            // SAFETY: `slot` is in bounds until it reaches `end`
+            unsafe {
+                acc = f(acc, self.at(slot));
+                slot = slot.add(1);
+            }
+        }
+        acc
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+impl DoubleEndedIterator for Utf8CheckedArgs {
+    #[inline]
+    fn next_back(&mut self) -> Option<Result<&'static str, InvalidArg>> {
+        if self.inner.cur == self.inner.end {
+            return None;
+        }
+        // SAFETY: the iterator isn't empty, so the last slot is in bounds
+        self.inner.end = unsafe { self.inner.end.sub(1) };
+        Some(unsafe { self.at(self.inner.end) })
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Result<&'static str, InvalidArg>> {
+        if n >= self.inner.len() {
+            self.inner.end = self.inner.cur;
+            return None;
+        }
+        // SAFETY: just checked that `end - n` is in bounds
+        self.inner.end = unsafe { self.inner.end.sub(n) };
+        self.next_back()
+    }
+
+    #[inline]
+    fn rfold<B, F: FnMut(B, Result<&'static str, InvalidArg>) -> B>(
+        self,
+        mut acc: B,
+        mut f: F
+    ) -> B {
+        let mut slot = self.inner.end;
+        while slot != self.inner.cur {
+            // SAFETY: `slot - 1` is in bounds until `slot` reaches `cur`
+            unsafe {
+                slot = slot.sub(1);
+                acc = f(acc, self.at(slot));
+            }
+        }
+        acc
+    }
+}
+
+impl ExactSizeIterator for Utf8CheckedArgs {
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl FusedIterator for Utf8CheckedArgs {}
@@ -0,0 +1,288 @@
+// detection of the kernel's argv layout, where the argument strings are back-to-back in one block.
+//  in that case each argument's length is the distance to the next one's start, and validating the
+//  whole block as UTF-8 validates every argument (a null byte can't be inside a multibyte
+//  sequence), so `Utf8CheckedArgs` can skip the per-argument `strlen` and validation. argv's slots
+//  can still be permuted or rewritten after that, e.g. by `getopt`, so each argument is checked
+//  against the block before its length is trusted.
+//
+// the block is checked once, for the startup argv only. anything else, including argv set by
+//  `set_argc_argv`, uses the normal path.
+
+#![cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+
+import! {
+    option::Option::{self, None, Some}
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+import! {
+    {
+        ptr,
+        sync::atomic::{AtomicPtr, AtomicU8, Ordering}
+    }
+}
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(feature = "assume_valid_str")))]
+import! {
+    sync::atomic::AtomicBool
+}
+
+use crate::ffi::strlen;
+
+/// A block of back-to-back argument strings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Block {
+    // the first argument's start
+    pub(crate) start: *const u8,
+    pub(crate) argv: *const *const u8,
+    pub(crate) argv_end: *const *const u8,
+    // one past the last argument's terminator
+    pub(crate) end: *const u8,
+    #[cfg(not(feature = "assume_valid_str"))]
+    pub(crate) utf8: bool
+}
+
+impl Block {
+    /// Gets the length of the argument in `slot` without scanning it, or `None` if the slot or the
+    /// next one no longer point to consecutive strings in the block.
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be in `argv..argv_end`, and the block's strings must still be readable.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) unsafe fn len_at(&self, slot: *const *const u8) -> Option<usize> {
+        let p = slot.read();
+        let next = slot.add(1);
+        let next = if next == self.argv_end { self.end } else { next.read() };
+        // `p` must start a string, so a slice from it is on a character boundary, and `next` must
+        //  follow a terminator. each byte is only read once it's known to be in the block.
+        if p >= self.start
+            && p < next
+            && next <= self.end
+            && (p == self.start || p.sub(1).read() == 0)
+            && next.sub(1).read() == 0
+        {
+            Some(next as usize - p as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Checks that argv's slots still point to the block's strings, in order, as when it was found.
+    /// The block holds exactly one string per slot, so any strictly increasing sequence of string
+    /// starts in it is the original one. This only reads the slots and one byte per argument.
+    ///
+    /// # Safety
+    ///
+    /// The block's slots and strings must still be readable.
+    #[inline]
+    pub(crate) unsafe fn intact(&self) -> bool {
+        let mut prev = self.start;
+        let mut slot = self.argv;
+        while slot != self.argv_end {
+            let p = slot.read();
+            let first = slot == self.argv;
+            if (first && p != self.start)
+                || (!first && (p <= prev || p >= self.end || p.sub(1).read() != 0))
+            {
+                return false;
+            }
+            prev = p;
+            slot = slot.add(1);
+        }
+        true
+    }
+
+    /// Whether the block was validated as UTF-8. It isn't with `assume_valid_str`.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn is_utf8(&self) -> bool {
+        #[cfg(not(feature = "assume_valid_str"))]
+        {
+            self.utf8
+        }
+        #[cfg(feature = "assume_valid_str")]
+        {
+            false
+        }
+    }
+
+    /// Whether the block's strings are still the arguments. Only the startup argv's block can stop
+    /// being so, when `set_process_title` overwrites it.
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    pub(crate) fn live(&self) -> bool {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            STATE.load(Ordering::Acquire) == SOME
+                || !ptr::eq(self.argv, ARGV.load(Ordering::Relaxed))
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            true
+        }
+    }
+}
+
+/// Checks whether the `argc` strings in `argv` are back-to-back, in order.
+///
+/// # Safety
+///
+/// `argv` must point to `argc` valid pointers to null-terminated strings.
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub(crate) unsafe fn detect(argc: u32, argv: *const *const u8) -> Option<Block> {
+    if argc == 0 || argv.is_null() {
+        return None;
+    }
+
+    let start = argv.read();
+    let mut end = start;
+    for i in 0..argc as usize {
+        let p = argv.add(i).read();
+        if p != end {
+            return None;
+        }
+        end = p.add(strlen(p) + 1);
+    }
+
+    #[cfg(not(feature = "assume_valid_str"))]
+    let utf8 = crate::str_checks::is_valid_utf8(switch!(core::slice::from_raw_parts(
+        start,
+        end as usize - start as usize
+    )));
+
+    Some(Block {
+        start,
+        argv,
+        argv_end: argv.add(argc as usize),
+        end,
+        #[cfg(not(feature = "assume_valid_str"))]
+        utf8
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const UNCHECKED: u8 = 0;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const CHECKING: u8 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const NONE: u8 = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SOME: u8 = 3;
+
+// the fields of the startup argv's block, valid once STATE is SOME
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static STATE: AtomicU8 = AtomicU8::new(UNCHECKED);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static START: AtomicPtr<u8> = AtomicPtr::new(ptr::null_mut());
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static ARGV: AtomicPtr<*const u8> = AtomicPtr::new(ptr::null_mut());
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static ARGV_END: AtomicPtr<*const u8> = AtomicPtr::new(ptr::null_mut());
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static END: AtomicPtr<u8> = AtomicPtr::new(ptr::null_mut());
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(feature = "assume_valid_str")))]
+static UTF8: AtomicBool = AtomicBool::new(false);
+
+/// Gets the block of the startup argv, checking it the first time, or `None` if it isn't
+/// contiguous or argv has been overridden.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub(crate) fn block() -> Option<Block> {
+    match STATE.load(Ordering::Acquire) {
+        SOME => Some(Block {
+            start: START.load(Ordering::Relaxed),
+            argv: ARGV.load(Ordering::Relaxed),
+            argv_end: ARGV_END.load(Ordering::Relaxed),
+            end: END.load(Ordering::Relaxed),
+            #[cfg(not(feature = "assume_valid_str"))]
+            utf8: UTF8.load(Ordering::Relaxed)
+        }),
+        UNCHECKED => check(),
+        _ => None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub(crate) fn block() -> Option<Block> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cold]
+#[inline(never)]
+fn check() -> Option<Block> {
+    use crate::direct::{ArgvSource, imp};
+
+    // another thread is checking; it's not worth waiting for
+    if STATE.compare_exchange(UNCHECKED, CHECKING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        return None;
+    }
+
+    // `imp` rather than `direct` so a test's thread override is never mistaken for the startup
+    //  argv. an override racing with this is still a valid argv, so checking it is harmless.
+    match imp::argv_source() {
+        ArgvSource::Init | ArgvSource::ProcSelfCmdline => {}
+        _ => {
+            // argv may be restored later, so check again next time
+            STATE.store(UNCHECKED, Ordering::Release);
+            return None;
+        }
+    }
+    let (argc, argv) = imp::argc_argv();
+
+    // SAFETY: the pair is the startup argv
+    let block = unsafe { detect(argc, argv) };
+    let state = match block {
+        Some(b) => {
+            START.store(b.start as *mut _, Ordering::Relaxed);
+            ARGV.store(b.argv as *mut _, Ordering::Relaxed);
+            ARGV_END.store(b.argv_end as *mut _, Ordering::Relaxed);
+            END.store(b.end as *mut _, Ordering::Relaxed);
+            #[cfg(not(feature = "assume_valid_str"))]
+            UTF8.store(b.utf8, Ordering::Relaxed);
+            SOME
+        }
+        None => NONE
+    };
+    // fails if `invalidate` ran in the meantime, which must win
+    let _ = STATE.compare_exchange(CHECKING, state, Ordering::Release, Ordering::Relaxed);
+    block
+}
+
+/// Stops using the startup argv's block, because its strings are about to be overwritten.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn invalidate() {
+    STATE.store(NONE, Ordering::Release);
+}
+
+/// Gets the startup argv's block if it contains every argument in `cur..end` and its slots are
+/// still intact.
+#[inline]
+pub(crate) fn covering(cur: *const *const u8, end: *const *const u8) -> Option<Block> {
+    match block() {
+        // SAFETY: the startup argv's slots and strings are never freed
+        Some(b) if cur >= b.argv && end <= b.argv_end && unsafe { b.intact() } => Some(b),
+        _ => None
+    }
+}
+
+/// Creates a [`Utf8CheckedArgs`](crate::Utf8CheckedArgs) over an explicit `argc` and `argv` which
+/// uses the contiguous fast path, or `None` if the strings aren't back-to-back or aren't all valid
+/// UTF-8.
+///
+/// # Safety
+///
+/// The same as [`Args::from_raw`](crate::Args::from_raw).
+#[cfg(feature = "__bench")]
+#[allow(clippy::must_use_candidate)]
+pub unsafe fn checked_contiguous(
+    argc: u32,
+    argv: *const *const u8
+) -> Option<crate::Utf8CheckedArgs> {
+    let block = detect(argc, argv).filter(Block::is_utf8)?;
+    Some(crate::Utf8CheckedArgs::with_block(crate::Args::from_raw(argc, argv), Some(block)))
+}
@@ -0,0 +1,220 @@
+#![allow(clippy::while_let_on_iterator, unused_qualifications)]
+
+import! {
+    {
+        default::Default,
+        iter::{FusedIterator, Iterator},
+        ops::Fn,
+        option::Option::{self, None, Some}
+    }
+}
+
+use crate::{CStr, direct, helpers};
+
+// not Copy for consistency with Args
+/// An iterator over the program's environment as `(key, value)` pairs.
+///
+/// The key is yielded as a byte slice rather than a [`CStr`] because it is terminated by the `=`
+/// separating it from the value, not by a null byte. The value is a
+/// <code>[CStr](CStr)<'static></code>. Entries without a `=` yield the whole entry as the key and
+/// an empty value.
+///
+/// This iterates over the environment the program was started with, as given by
+/// [`direct::envp`]; no allocation takes place.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Env {
+    pub(crate) cur: *const *const u8
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Env {
+    /// Creates a new `Env` instance.
+    #[must_use]
+    // cold because these are usually called once at startup
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn new() -> Env {
+        Env { cur: direct::envp() }
+    }
+
+    /// Gets the value of the environment variable `name`, or `None` if it is not set.
+    ///
+    /// If the variable is set multiple times, the first value is returned.
+    #[must_use]
+    pub fn get(name: &str) -> Option<CStr<'static>> {
+        let name = name.as_bytes();
+        let mut env = Env::new();
+        while let Some((k, v)) = env.next() {
+            if k == name {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    /// Map this iterator to a different type. Like [`MappedEnv::new`], but operates on an existing
+    /// iterator.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn map_ty<Ret, F: Fn(*const u8) -> Option<Ret>>(&self, map: F) -> MappedEnv<Ret, F> {
+        MappedEnv { cur: self.cur, map }
+    }
+
+    /// Map this iterator to `(&'static str, &'static str)`. Like [`MappedEnv::utf8`], but operates
+    /// on an existing iterator. Entries whose key or value is not valid UTF-8 are skipped.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    #[allow(clippy::type_complexity)]
+    pub fn map_utf8(
+        &self
+    ) -> MappedEnv<
+        (&'static str, &'static str),
+        fn(*const u8) -> Option<(&'static str, &'static str)>
+    > {
+        MappedEnv { cur: self.cur, map: helpers::env_to_str }
+    }
+
+    #[cfg(feature = "std")]
+    /// Map this iterator to `(&'static OsStr, &'static OsStr)`. Like [`MappedEnv::os`], but
+    /// operates on an existing iterator.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    #[allow(clippy::type_complexity)]
+    pub fn map_os(
+        &self
+    ) -> MappedEnv<
+        (&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr),
+        fn(*const u8) -> Option<(&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr)>
+    > {
+        MappedEnv { cur: self.cur, map: helpers::env_to_osstr }
+    }
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next_raw(&mut self) -> Option<*const u8> {
+        if self.cur.is_null() {
+            return None;
+        }
+
+        // SAFETY: `cur` is non-null and points into envp, which is terminated by a null pointer we
+        //  never advance past
+        let p = unsafe { self.cur.read() };
+        if p.is_null() {
+            return None;
+        }
+        self.cur = unsafe { self.cur.add(1) };
+
+        Some(p)
+    }
+}
+
+impl Iterator for Env {
+    type Item = (&'static [u8], CStr<'static>);
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<(&'static [u8], CStr<'static>)> {
+        // SAFETY: the pointer is from envp, which always contains valid pointers to cstrs
+        self.next_raw().map(|p| unsafe { helpers::split_env(p) })
+    }
+}
+
+impl FusedIterator for Env {}
+
+// not Copy for consistency with Args
+/// An iterator that maps each environment entry using a user-provided function. If the mapping
+/// returns `None`, that entry is skipped.
+///
+/// The mapping function receives a pointer to the whole null-terminated `KEY=value` entry.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MappedEnv<Ret, F: Fn(*const u8) -> Option<Ret> = fn(*const u8) -> Option<Ret>> {
+    pub(crate) cur: *const *const u8,
+    pub(crate) map: F
+}
+
+impl
+    MappedEnv<(&'static str, &'static str), fn(*const u8) -> Option<(&'static str, &'static str)>>
+{
+    /// Returns an iterator over the program's environment as `(&'static str, &'static str)`.
+    /// Entries whose key or value is not valid UTF-8 are skipped.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    #[allow(clippy::type_complexity)]
+    pub fn utf8() -> MappedEnv<
+        (&'static str, &'static str),
+        fn(*const u8) -> Option<(&'static str, &'static str)>
+    > {
+        MappedEnv::new(helpers::env_to_str)
+    }
+}
+
+#[cfg(feature = "std")]
+impl
+    MappedEnv<
+        (&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr),
+        fn(*const u8) -> Option<(&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr)>
+    >
+{
+    /// Returns an iterator over the program's environment as
+    /// `(&'static std::ffi::OsStr, &'static std::ffi::OsStr)`. Requires the `std` feature.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    #[allow(clippy::type_complexity)]
+    pub fn os() -> MappedEnv<
+        (&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr),
+        fn(*const u8) -> Option<(&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr)>
+    > {
+        MappedEnv::new(helpers::env_to_osstr)
+    }
+}
+
+impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedEnv<Ret, F> {
+    /// Returns an iterator that applies `map` to each environment entry (`*const u8`). If `map`
+    /// returns `None`, that entry is skipped.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn new(map: F) -> MappedEnv<Ret, F> {
+        MappedEnv { cur: direct::envp(), map }
+    }
+
+    /// Converts this mapped iterator to an [`Env`] instance. Like [`Env::new`], but operates on an
+    /// existing mapped iterator.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn unmap(self) -> Env {
+        Env { cur: self.cur }
+    }
+}
+
+impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedEnv<Ret, F> {
+    type Item = Ret;
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<Ret> {
+        if self.cur.is_null() {
+            return None;
+        }
+
+        loop {
+            // SAFETY: `cur` is non-null and points into envp, which is terminated by a null pointer
+            //  we never advance past
+            let p = unsafe { self.cur.read() };
+            if p.is_null() {
+                return None;
+            }
+            self.cur = unsafe { self.cur.add(1) };
+
+            // SAFETY: the pointer is from envp, which always contains valid pointers to cstrs
+            if let Some(v) = (self.map)(p) {
+                return Some(v);
+            }
+        }
+    }
+}
+
+impl<Ret, F: Fn(*const u8) -> Option<Ret>> FusedIterator for MappedEnv<Ret, F> {}
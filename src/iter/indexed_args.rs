@@ -0,0 +1,483 @@
+#![allow(clippy::while_let_on_iterator)]
+
+import! {
+    {
+        iter::{ExactSizeIterator, FusedIterator, Iterator},
+        ops::{Fn, FnMut},
+        option::Option::{self, None, Some}
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+import! {
+    iter::DoubleEndedIterator
+}
+
+use crate::{
+    Args,
+    CStr,
+    Fallibility,
+    Fallible,
+    Infallible,
+    MappedArgs,
+    direct,
+    helpers,
+    iter::len
+};
+
+/// An iterator that maps each argument using a user-provided function which also receives the
+/// argument's index in argv. If the mapping returns `None`, that argument is skipped, but the
+/// indices passed to later calls are unaffected, unlike with [`Iterator::enumerate`].
+///
+/// Created by [`MappedArgs::new_indexed`], [`Args::map_indexed`], [`MappedArgs::indexed`] and
+/// [`Args::indexed`].
+///
+/// # Examples
+///
+/// ```
+/// let argv = snailx::argv!["prog", "a", "b"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// // skip the program name, but keep reporting argv positions
+/// let args = args.map_utf8().indexed().filter(|&(_, a)| a != "prog");
+/// assert_eq!(args.collect::<Vec<_>>(), [(1, "a"), (2, "b")]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IndexedArgs<Ret, F: Fn(usize, *const u8) -> Option<Ret>, M: Fallibility = Fallible> {
+    pub(crate) cur: *const *const u8,
+    pub(crate) end: *const *const u8,
+    // the slot of index 0
+    pub(crate) base: *const *const u8,
+    pub(crate) map: F,
+    pub(crate) fallibility: M
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn indexed_cstr(i: usize, p: *const u8) -> Option<(usize, CStr<'static>)> {
+    // SAFETY: only called with pointers from argv
+    Some((i, unsafe { CStr::from_ptr(p) }))
+}
+
+// on a concrete type, as `MappedArgs`'s own map can't take an index, so that `new_indexed` can
+//  still be called as `MappedArgs::new_indexed`
+impl MappedArgs<(), fn(*const u8) -> Option<()>> {
+    /// Returns an iterator that applies `map` to each argument's index in argv and pointer
+    /// (`*const u8`). If `map` returns `None`, that argument is skipped.
+    ///
+    /// The mapping function is assumed to be fallible, so `size_hint()` will return
+    /// `(0, Some(len))`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let args = snailx::MappedArgs::new_indexed(|i, p| {
+    ///     // SAFETY: `p` is from argv
+    ///     let arg = unsafe { snailx::CStr::from_ptr(p) };
+    ///     match arg.to_str() {
+    ///         Ok(s) => Some(s),
+    ///         Err(_) => {
+    ///             eprintln!("warning: argv[{}] is not valid UTF-8", i);
+    ///             None
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn new_indexed<Ret, F: Fn(usize, *const u8) -> Option<Ret>>(map: F) -> IndexedArgs<Ret, F> {
+        let (argc, argv) = direct::argc_argv();
+        IndexedArgs {
+            cur: argv,
+            end: helpers::back(argv, argc),
+            base: argv,
+            map,
+            fallibility: Fallible
+        }
+    }
+
+    /// Returns an iterator that applies `map` to each argument's index in argv and pointer
+    /// (`*const u8`).
+    ///
+    /// The mapping function is assumed to be infallible, so the iterator is an
+    /// [`ExactSizeIterator`].
+    ///
+    /// # Safety
+    ///
+    /// `map` must never return `None`.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub unsafe fn new_indexed_infallible<Ret, F: Fn(usize, *const u8) -> Option<Ret>>(
+        map: F
+    ) -> IndexedArgs<Ret, F, Infallible> {
+        let (argc, argv) = direct::argc_argv();
+        IndexedArgs {
+            cur: argv,
+            end: helpers::back(argv, argc),
+            base: argv,
+            map,
+            fallibility: Infallible
+        }
+    }
+}
+
+impl<Ret, F: Fn(*const u8) -> Option<Ret>, M: Fallibility> MappedArgs<Ret, F, M> {
+    /// Pairs each item with its argument's index, which, unlike [`Iterator::enumerate`], still
+    /// counts arguments the mapping function skipped. Indices are positions in argv if the
+    /// arguments are the program's own, and otherwise count from the first remaining argument.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn indexed(
+        self
+    ) -> IndexedArgs<(usize, Ret), impl Fn(usize, *const u8) -> Option<(usize, Ret)>, M> {
+        let map = self.map;
+        IndexedArgs {
+            cur: self.cur,
+            end: self.end,
+            base: crate::iter::argv_base(self.cur),
+            map: move |i, p| map(p).map(|v| (i, v)),
+            fallibility: self.fallibility
+        }
+    }
+}
+
+impl Args {
+    /// Map this iterator using a function which also receives each argument's index. Like
+    /// [`MappedArgs::new_indexed`], but operates on an existing iterator. Indices are still
+    /// positions in argv if its arguments are the program's own, and otherwise count from its first
+    /// remaining argument.
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn map_indexed<Ret, F: Fn(usize, *const u8) -> Option<Ret>>(
+        &self,
+        map: F
+    ) -> IndexedArgs<Ret, F> {
+        IndexedArgs {
+            cur: self.cur,
+            end: self.end,
+            base: crate::iter::argv_base(self.cur),
+            map,
+            // assume fallible for safety
+            fallibility: Fallible
+        }
+    }
+
+    /// Pairs each argument with its index. Like [`Iterator::enumerate`], but indices are positions
+    /// in argv, as for [`Args::map_indexed`], and aren't shifted by `nth` or `next_back`.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn indexed(
+        &self
+    ) -> IndexedArgs<
+        (usize, CStr<'static>),
+        fn(usize, *const u8) -> Option<(usize, CStr<'static>)>,
+        Infallible
+    > {
+        IndexedArgs {
+            cur: self.cur,
+            end: self.end,
+            base: crate::iter::argv_base(self.cur),
+            map: indexed_cstr,
+            fallibility: Infallible
+        }
+    }
+}
+
+impl<Ret, F: Fn(usize, *const u8) -> Option<Ret>, M: Fallibility> IndexedArgs<Ret, F, M> {
+    // SAFETY: `slot` must be in `base..end`
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    unsafe fn at(&self, slot: *const *const u8) -> Option<Ret> {
+        (self.map)(len(self.base, slot), slot.read())
+    }
+
+    /// Gets the remaining length of items in this iterator, if it is known exactly.
+    ///
+    /// Returns `None` if this iterator is [`Fallible`], or `Some(len)` if it is [`Infallible`]. For
+    /// the latter, [`ExactSizeIterator::len`] returns the length directly.
+    #[must_use]
+    pub fn exact_len(&self) -> Option<usize> {
+        fallible_q! {
+            M,
+            {
+                None
+            },
+            {
+                Some(unsafe { len(self.cur, self.end) })
+            }
+        }
+    }
+
+    /// Gets the element at index `i` from the current position, or `None` if the index is
+    /// out-of-bounds or the mapping function returns `None`. This does _not_ consume elements like
+    /// `nth`.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<Ret> {
+        // SAFETY: just checked that `cur + i` is in bounds
+        if unsafe { len(self.cur, self.end) } > i {
+            unsafe { self.at(self.cur.add(i)) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<Ret, F: Fn(usize, *const u8) -> Option<Ret>, M: Fallibility> Iterator
+    for IndexedArgs<Ret, F, M>
+{
+    type Item = Ret;
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<Ret> {
+        while self.cur != self.end {
+            let p = self.cur;
+            // SAFETY: we just checked that `self.cur < self.end`
+            self.cur = unsafe { self.cur.add(1) };
+            assume!(!p.is_null() && p < self.end);
+
+            // SAFETY: the pointer is from argv, which always contains valid pointers to cstrs
+            if let Some(v) = unsafe { self.at(p) } {
+                return Some(v);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the bounds on the remaining length of the iterator, like
+    /// [`MappedArgs::size_hint`](MappedArgs#method.size_hint).
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = unsafe { len(self.cur, self.end) };
+        fallible_q! {
+            M,
+            {
+                (0, Some(len))
+            },
+            {
+                (len, Some(len))
+            }
+        }
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        if M::FALLIBLE {
+            self.fold(0, |count, _| count + 1)
+        } else {
+            // SAFETY: the pointers are guaranteed to be valid for len() as they are from argv
+            unsafe { len(self.cur, self.end) }
+        }
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Ret> {
+        while self.cur != self.end {
+            // SAFETY: we just checked that `self.cur < self.end`
+            self.end = unsafe { self.end.sub(1) };
+            assume!(!self.end.is_null() && self.end > self.cur);
+
+            if let Some(v) = unsafe { self.at(self.end) } {
+                return Some(v);
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Ret> {
+        // SAFETY: the pointers are guaranteed to be valid for len() as they are from argv
+        if n >= unsafe { len(self.cur, self.end) } {
+            self.cur = self.end;
+            return None;
+        }
+
+        fallible_q!(
+            M,
+            {
+                let mut i = 0;
+                while self.cur != self.end {
+                    let p = self.cur;
+                    // SAFETY: we just checked that `self.cur < self.end`
+                    self.cur = unsafe { self.cur.add(1) };
+                    assume!(!p.is_null() && p < self.end);
+
+                    if let Some(v) = unsafe { self.at(p) } {
+                        if i == n {
+                            return Some(v);
+                        }
+                        i += 1;
+                    }
+                }
+            },
+            {
+                // SAFETY: we just checked that `self.cur + n` is in bounds
+                self.cur = unsafe { self.cur.add(n) };
+                assume!(!self.cur.is_null() && self.cur < self.end);
+
+                return self.next();
+            }
+        );
+
+        None
+    }
+
+    #[inline]
+    fn fold<B, G: FnMut(B, Ret) -> B>(mut self, mut acc: B, mut f: G) -> B {
+        if self.cur == self.end {
+            return acc;
+        }
+
+        // the index is tracked here rather than recomputed from the pointer for every argument
+        let mut i = unsafe { len(self.base, self.cur) };
+        loop {
+            assume!(!self.cur.is_null() && self.cur < self.end);
+            fallible_q!(
+                M,
+                {
+                    if let Some(v) = (self.map)(i, unsafe { self.cur.read() }) {
+                        acc = f(acc, v);
+                    }
+                },
+                {
+                    // SAFETY: caller guarantees that the map is infallible
+                    acc = f(
+                        acc,
+                        assume!(
+                            car,
+                            Some,
+                            e,
+                            (self.map)(i, unsafe { self.cur.read() }),
+                            "map is infallible, but returned None"
+                        )
+                    );
+                }
+            );
+
+            // SAFETY: we just checked that `self.cur` is in bounds
+            self.cur = unsafe { self.cur.add(1) };
+            i += 1;
+            if self.cur == self.end {
+                break;
+            }
+        }
+        acc
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+impl<Ret, F: Fn(usize, *const u8) -> Option<Ret>, M: Fallibility> DoubleEndedIterator
+    for IndexedArgs<Ret, F, M>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Ret> {
+        while self.cur != self.end {
+            // SAFETY: we just checked that `self.cur < self.end`
+            self.end = unsafe { self.end.sub(1) };
+            assume!(!self.end.is_null() && self.end > self.cur);
+
+            if let Some(v) = unsafe { self.at(self.end) } {
+                return Some(v);
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Ret> {
+        if n >= unsafe { len(self.cur, self.end) } {
+            self.end = self.cur;
+            return None;
+        }
+
+        fallible_q!(
+            M,
+            {
+                let mut i = 0;
+                while self.cur != self.end {
+                    self.end = unsafe { self.end.sub(1) };
+                    assume!(!self.end.is_null() && self.end > self.cur);
+
+                    if let Some(v) = unsafe { self.at(self.end) } {
+                        if i == n {
+                            return Some(v);
+                        }
+                        i += 1;
+                    }
+                }
+            },
+            {
+                self.end = unsafe { self.end.sub(n) };
+                assume!(!self.end.is_null() && self.end > self.cur);
+
+                return self.next_back();
+            }
+        );
+
+        None
+    }
+
+    #[inline]
+    fn rfold<B, G: FnMut(B, Ret) -> B>(mut self, mut acc: B, mut f: G) -> B {
+        if self.cur == self.end {
+            return acc;
+        }
+
+        let mut i = unsafe { len(self.base, self.end) };
+        loop {
+            // SAFETY: we just checked that `self.cur < self.end` in the last loop
+            self.end = unsafe { self.end.sub(1) };
+            i -= 1;
+            assume!(!self.end.is_null() && self.end > self.cur);
+
+            fallible_q! {
+                M,
+                {
+                    if let Some(v) = (self.map)(i, unsafe { self.end.read() }) {
+                        acc = f(acc, v);
+                    }
+                },
+                {
+                    // SAFETY: caller guarantees that the map is infallible
+                    acc = f(
+                        acc,
+                        assume!(
+                            car,
+                            Some,
+                            e,
+                            (self.map)(i, unsafe { self.end.read() }),
+                            "map is infallible, but returned None"
+                        )
+                    );
+                }
+            }
+
+            if self.cur == self.end {
+                break;
+            }
+        }
+        acc
+    }
+}
+
+impl<Ret, F: Fn(usize, *const u8) -> Option<Ret>> ExactSizeIterator
+    for IndexedArgs<Ret, F, Infallible>
+{
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn len(&self) -> usize {
+        // SAFETY: the pointers are guaranteed to be valid for len() as they are from argv
+        unsafe { len(self.cur, self.end) }
+    }
+}
+impl<Ret, F: Fn(usize, *const u8) -> Option<Ret>, M: Fallibility> FusedIterator
+    for IndexedArgs<Ret, F, M>
+{
+}
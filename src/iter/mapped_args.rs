@@ -2,7 +2,13 @@
 
 import! {
     {
-        iter::{Iterator, FusedIterator},
+        clone::Clone,
+        cmp::{Eq, Ord, PartialEq, PartialOrd},
+        default::Default,
+        fmt::Debug,
+        hash::Hash,
+        iter::{ExactSizeIterator, Iterator, FusedIterator},
+        marker::Copy,
         ops::{Fn, FnMut},
         option::Option::{self, None, Some}
     }
@@ -14,23 +20,10 @@ import! {
 }
 
 use {
-    crate::{Args, helpers, direct, iter::len},
+    crate::{Args, Utf8Chunks, iter::len},
 };
 // TODO: may be better to not implement certain things manually and just delegate to fold
 
-macro_rules! fallible_q {
-    ($self:ident, $f:expr, $i:expr) => {
-        #[cfg(not(feature = "infallible_map"))]
-        $f
-        #[cfg(feature = "infallible_map")]
-        if $self.fallible {
-            $f
-        } else {
-            $i
-        }
-    };
-}
-
 macro_rules! next_back {
     ($self:ident) => {{
         while $self.cur != $self.end {
@@ -48,25 +41,131 @@ macro_rules! next_back {
     }};
 }
 
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Whether the mapping function of a [`MappedArgs`] can return `None`: either [`Fallible`] or
+/// [`Infallible`]. This trait is sealed.
+pub trait Fallibility:
+    sealed::Sealed + Copy + Clone + Debug + Default + PartialEq + Eq + PartialOrd + Ord + Hash
+{
+    /// Whether the mapping function may return `None`.
+    const FALLIBLE: bool;
+}
+
+/// Marks a [`MappedArgs`] whose mapping function may return `None`, skipping that argument. The
+/// number of items isn't known until the iterator is consumed, so `size_hint()` returns
+/// `(0, Some(len))`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fallible;
+
+/// Marks a [`MappedArgs`] whose mapping function never returns `None`. It yields exactly one item
+/// per argument, so it implements [`ExactSizeIterator`], and `nth` and `count` don't need to call
+/// the mapping function for the arguments they skip.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Infallible;
+
+impl sealed::Sealed for Fallible {}
+impl sealed::Sealed for Infallible {}
+
+impl Fallibility for Fallible {
+    const FALLIBLE: bool = true;
+}
+impl Fallibility for Infallible {
+    const FALLIBLE: bool = false;
+}
+
+/// The [`Fallibility`] of [`MappedArgs::utf8`] and [`Args::map_utf8`]: [`Infallible`] with the
+/// `assume_valid_str` feature, which makes every argument valid UTF-8, and [`Fallible`] otherwise.
+#[cfg(not(feature = "assume_valid_str"))]
+pub type Utf8Fallibility = Fallible;
+/// The [`Fallibility`] of [`MappedArgs::utf8`] and [`Args::map_utf8`]: [`Infallible`] with the
+/// `assume_valid_str` feature, which makes every argument valid UTF-8, and [`Fallible`] otherwise.
+#[cfg(feature = "assume_valid_str")]
+pub type Utf8Fallibility = Infallible;
+
 // not Copy for consistency with Args
 /// An iterator that maps each argument using a user-provided function. If the mapping returns
 /// `None`, that argument is skipped.
+///
+/// Whether the mapping function can fail is part of the type, through `M`: [`Fallible`] or
+/// [`Infallible`].
+///
+/// Like [`Args`], every constructor reads `(argc, argv)` once, as a consistent pair.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct MappedArgs<Ret, F: Fn(*const u8) -> Option<Ret> = fn(*const u8) -> Option<Ret>> {
+pub struct MappedArgs<
+    Ret,
+    F: Fn(*const u8) -> Option<Ret> = fn(*const u8) -> Option<Ret>,
+    M: Fallibility = Fallible
+> {
     pub(crate) cur: *const *const u8,
     pub(crate) end: *const *const u8,
     pub(crate) map: F,
-    #[cfg(feature = "infallible_map")]
-    pub(crate) fallible: bool
+    pub(crate) fallibility: M
 }
 
 impl MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>> {
     /// Returns an iterator over the program's arguments as `&'static str`. Non-UTF-8 arguments are
     /// skipped.
     #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn utf8() -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>, Utf8Fallibility>
+    {
+        Args::new().map_utf8()
+    }
+
+    /// Returns an iterator over the program's arguments as `&'static str`, without checking that
+    /// they are valid UTF-8. This is what the `assume_valid_str` feature does to
+    /// [`utf8`](MappedArgs::utf8), but for this call only.
+    ///
+    /// Arguments are checked in debug builds, which panic on invalid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// Every argument must be valid UTF-8. Since arguments come from whoever ran the program, this
+    /// is usually only known if the program was started by a trusted parent with fixed arguments.
+    #[must_use]
     #[cfg_attr(not(feature = "no_cold"), cold)]
-    pub fn utf8() -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>> {
-        MappedArgs::new(helpers::try_to_str)
+    #[allow(clippy::type_complexity)]
+    pub unsafe fn utf8_unchecked()
+    -> MappedArgs<&'static str, fn(*const u8) -> Option<&'static str>, Infallible> {
+        Args::new().map_utf8_unchecked()
+    }
+}
+
+impl MappedArgs<&'static [u8], fn(*const u8) -> Option<&'static [u8]>> {
+    /// Returns an iterator over the program's arguments as `&'static [u8]`, without the null
+    /// terminator. Unlike [`utf8`](MappedArgs::utf8), this doesn't validate or skip anything.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn bytes() -> MappedArgs<&'static [u8], fn(*const u8) -> Option<&'static [u8]>, Infallible>
+    {
+        Args::new().map_bytes()
+    }
+}
+
+impl MappedArgs<Utf8Chunks<'static>, fn(*const u8) -> Option<Utf8Chunks<'static>>> {
+    /// Returns an iterator over the program's arguments as [`Utf8Chunks`], which split each one
+    /// into valid UTF-8 and invalid byte runs. Unlike [`utf8`](MappedArgs::utf8), no argument is
+    /// skipped, so positions are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// for (i, arg) in snailx::MappedArgs::utf8_lossy().enumerate() {
+    ///     println!("{}: {}", i, arg.display());
+    /// }
+    /// ```
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn utf8_lossy()
+    -> MappedArgs<Utf8Chunks<'static>, fn(*const u8) -> Option<Utf8Chunks<'static>>, Infallible>
+    {
+        Args::new().map_utf8_lossy()
     }
 }
 
@@ -75,19 +174,14 @@ impl MappedArgs<&'static ::std::ffi::OsStr, fn(*const u8) -> Option<&'static ::s
     /// Returns an iterator over the program's arguments as `&'static std::ffi::OsStr`. Requires the
     /// `std` feature.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
-    pub fn os()
-    -> MappedArgs<&'static ::std::ffi::OsStr, fn(*const u8) -> Option<&'static ::std::ffi::OsStr>>
-    {
-        #[cfg(not(feature = "infallible_map"))]
-        {
-            MappedArgs::new(helpers::to_osstr)
-        }
-        #[cfg(feature = "infallible_map")]
-        unsafe {
-            // SAFETY: to_osstr only returns Some
-            MappedArgs::new_infallible(helpers::to_osstr)
-        }
+    pub fn os() -> MappedArgs<
+        &'static ::std::ffi::OsStr,
+        fn(*const u8) -> Option<&'static ::std::ffi::OsStr>,
+        Infallible
+    > {
+        Args::new().map_os()
     }
 }
 
@@ -96,21 +190,17 @@ impl MappedArgs<&'static crate::StdCStr, fn(*const u8) -> Option<&'static crate:
     /// Returns an iterator over the program's arguments as `&'static CStr`. Requires the
     /// `std` or `to_core_cstr` feature.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
-    pub fn std_cstr()
-    -> MappedArgs<&'static crate::StdCStr, fn(*const u8) -> Option<&'static crate::StdCStr>> {
-        #[cfg(not(feature = "infallible_map"))]
-        {
-            MappedArgs::new(helpers::to_stdcstr)
-        }
-        #[cfg(feature = "infallible_map")]
-        unsafe {
-            MappedArgs::new_infallible(helpers::to_stdcstr)
-        }
+    pub fn std_cstr() -> MappedArgs<
+        &'static crate::StdCStr,
+        fn(*const u8) -> Option<&'static crate::StdCStr>,
+        Infallible
+    > {
+        Args::new().map_stdlib_cstr()
     }
 }
 
-#[allow(clippy::len_without_is_empty)]
 impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedArgs<Ret, F> {
     /// Returns an iterator that applies `map` to each argument (`*const u8`). If `map` returns
     /// `None`, that argument is skipped.
@@ -120,32 +210,51 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedArgs<Ret, F> {
     #[must_use]
     #[cfg_attr(not(feature = "no_cold"), cold)]
     pub fn new(map: F) -> MappedArgs<Ret, F> {
-        let (argc, argv) = direct::argc_argv();
-        MappedArgs {
-            cur: argv,
-            end: helpers::back(argv, argc),
-            map,
-            #[cfg(feature = "infallible_map")]
-            fallible: true
-        }
+        Args::new().map_ty(map)
     }
 
     #[cfg(feature = "infallible_map")]
+    /// Returns an iterator that applies `map` to each argument (`*const u8`). Like
+    /// [`new`](MappedArgs::new), which it now is: infallibility is part of [`MappedArgs`]'s type,
+    /// so see [`new_exact`](MappedArgs::new_exact) for the infallible version.
+    ///
+    /// # Safety
+    ///
+    /// `map` must never return `None`.
+    #[deprecated(note = "use `new`, or `new_exact` for an infallible map")]
+    #[must_use]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub unsafe fn new_infallible(map: F) -> MappedArgs<Ret, F> {
+        Args::new().map_ty(map)
+    }
+
+    /// Gets the remaining length of items in this iterator. This is always `None`, since a
+    /// [`Fallible`] iterator's length isn't known until it is consumed.
+    #[deprecated(note = "use `exact_len`, or `ExactSizeIterator::len` on an `Infallible` iterator")]
+    #[allow(clippy::len_without_is_empty)]
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        self.exact_len()
+    }
+}
+
+impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedArgs<Ret, F, Infallible> {
     /// Returns an iterator that applies `map` to each argument (`*const u8`).
     ///
-    /// The mapping function is assumed to be infallible, so `size_hint()` will return
-    /// `(len, Some(len))`.
+    /// The mapping function is assumed to be infallible, so the iterator is an
+    /// [`ExactSizeIterator`].
     ///
     /// # Safety
     ///
     /// `map` must never return `None`.
     #[must_use]
     #[cfg_attr(not(feature = "no_cold"), cold)]
-    pub unsafe fn new_infallible(map: F) -> MappedArgs<Ret, F> {
-        let (argc, argv) = direct::argc_argv();
-        MappedArgs { cur: argv, end: helpers::back(argv, argc), map, fallible: false }
+    pub unsafe fn new_exact(map: F) -> MappedArgs<Ret, F, Infallible> {
+        Args::new().map_ty_exact(map)
     }
+}
 
+impl<Ret, F: Fn(*const u8) -> Option<Ret>, M: Fallibility> MappedArgs<Ret, F, M> {
     /// Converts this mapped iterator to an [`Args`] instance. Like [`Args::new`], but operates on
     /// an existing mapped iterator.
     #[must_use]
@@ -154,16 +263,54 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedArgs<Ret, F> {
         Args { cur: self.cur, end: self.end }
     }
 
+    /// Drops the first argument, which is the program name for a fresh iterator. Like
+    /// [`Args::without_prog`].
+    #[must_use]
+    #[inline]
+    pub fn without_prog(mut self) -> MappedArgs<Ret, F, M> {
+        if self.cur != self.end {
+            // SAFETY: just checked that `cur` is in bounds
+            self.cur = unsafe { self.cur.add(1) };
+        }
+        self
+    }
+
+    /// Splits this iterator at its first `--` argument, which is in neither half. Like
+    /// [`Args::split_at_double_dash`]; arguments are checked for `--` before mapping, so one the
+    /// mapping function would skip still splits.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn split_at_double_dash(self) -> (MappedArgs<Ret, F, M>, Option<MappedArgs<Ret, F, M>>)
+    where
+        F: Clone
+    {
+        // SAFETY: the pointers are from argv
+        match unsafe { crate::iter::find_double_dash(self.cur, self.end) } {
+            Some(dd) => {
+                let rest = MappedArgs {
+                    // SAFETY: `dd` is in bounds, so one past it is at most `end`
+                    cur: unsafe { dd.add(1) },
+                    end: self.end,
+                    map: self.map.clone(),
+                    fallibility: self.fallibility
+                };
+                (MappedArgs { end: dd, ..self }, Some(rest))
+            }
+            None => (self, None)
+        }
+    }
+
     // as_slice removed as it was pretty useless
 
-    /// Gets the remaining length of items in this iterator.
+    /// Gets the remaining length of items in this iterator, if it is known exactly.
     ///
-    /// Returns `None` if `infallible_map` is disabled or this iterator's mapping function is marked
-    /// as fallible. If `infallible_map` is enabled and this iterator is marked as infallible,
-    /// returns `Some(len)`.
-    pub fn len(&self) -> Option<usize> {
+    /// Returns `None` if this iterator is [`Fallible`], or `Some(len)` if it is [`Infallible`]. For
+    /// the latter, [`ExactSizeIterator::len`] returns the length directly.
+    #[must_use]
+    pub fn exact_len(&self) -> Option<usize> {
         fallible_q! {
-            self,
+            M,
             {
                 None
             },
@@ -198,7 +345,7 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> MappedArgs<Ret, F> {
     // TODO: skipping_[get/get_unchecked]
 }
 
-impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
+impl<Ret, F: Fn(*const u8) -> Option<Ret>, M: Fallibility> Iterator for MappedArgs<Ret, F, M> {
     type Item = Ret;
 
     // TODO: try rewriting these to be faster
@@ -229,15 +376,14 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
     /// The upper bound will always be `Some(len)`, where `len` is the number of elements remaining
     /// in the iterator if the mapping function returns `Some` for every element.
     ///
-    /// If `infallible_map` is disabled or this iterator's mapping function has been marked as
-    /// fallible, the lower bound will be 0. If `infallible_map` is enabled and this iterator is
-    /// marked as infallible, the lower bound will also be `len`.
+    /// If this iterator is [`Fallible`], the lower bound will be 0. If it is [`Infallible`], the
+    /// lower bound will also be `len`.
     #[allow(clippy::inline_always)]
     #[inline(always)]
     fn size_hint(&self) -> (usize, Option<usize>) {
         let len = unsafe { len(self.cur, self.end) };
         fallible_q! {
-            self,
+            M,
             {
                 (0, Some(len))
             },
@@ -248,10 +394,9 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
     }
 
     // default impl has an attr i don't want to override unless necessary
-    #[cfg(feature = "infallible_map")]
     #[inline]
     fn count(self) -> usize {
-        if self.fallible {
+        if M::FALLIBLE {
             self.fold(0, |count, _| count + 1)
         } else {
             // SAFETY: the pointers are guaranteed to be valid for len() as they are from argv
@@ -280,7 +425,7 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
         }
 
         fallible_q!(
-            self,
+            M,
             {
                 let mut i = 0;
                 while self.cur != self.end {
@@ -320,7 +465,7 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
         loop {
             assume!(!self.cur.is_null() && self.cur < self.end);
             fallible_q!(
-                self,
+                M,
                 {
                     if let Some(v) = (self.map)(unsafe { self.cur.read() }) {
                         acc = f(acc, v);
@@ -352,7 +497,9 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> Iterator for MappedArgs<Ret, F> {
 }
 
 #[cfg(feature = "rev_iter")]
-impl<Ret, F: Fn(*const u8) -> Option<Ret>> DoubleEndedIterator for MappedArgs<Ret, F> {
+impl<Ret, F: Fn(*const u8) -> Option<Ret>, M: Fallibility> DoubleEndedIterator
+    for MappedArgs<Ret, F, M>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Ret> {
         next_back!(self)
@@ -366,7 +513,7 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> DoubleEndedIterator for MappedArgs<Re
         }
 
         fallible_q!(
-            self,
+            M,
             {
                 let mut i = 0;
                 while self.cur != self.end {
@@ -404,7 +551,7 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> DoubleEndedIterator for MappedArgs<Re
             assume!(!self.end.is_null() && self.end > self.cur);
 
             fallible_q! {
-                self,
+                M,
                 {
                     // SAFETY: the pointer is from argv, which always contains valid pointers to
                     // cstrs
@@ -435,16 +582,12 @@ impl<Ret, F: Fn(*const u8) -> Option<Ret>> DoubleEndedIterator for MappedArgs<Re
     }
 }
 
-impl<Ret, F: Fn(*const u8) -> Option<Ret>> FusedIterator for MappedArgs<Ret, F> {}
-
-// removed as i realized neither of these fit the functionality of MappedArgs
-//
-// impl<Ret, F: Fn(*const u8) -> Option<Ret>> ExactSizeIterator
-//     for MappedArgs<Ret, F>
-// {
-//     #[allow(clippy::inline_always)]
-//     #[inline(always)]
-//     fn len(&self) -> usize {
-//         len(self.cur, self.end)
-//     }
-// }
+impl<Ret, F: Fn(*const u8) -> Option<Ret>> ExactSizeIterator for MappedArgs<Ret, F, Infallible> {
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn len(&self) -> usize {
+        // SAFETY: the pointers are guaranteed to be valid for len() as they are from argv
+        unsafe { len(self.cur, self.end) }
+    }
+}
+impl<Ret, F: Fn(*const u8) -> Option<Ret>, M: Fallibility> FusedIterator for MappedArgs<Ret, F, M> {}
@@ -1,7 +1,24 @@
 // TODO: try to implement try_fold/_rfold for both, reduce copies/ops in general
 
+import! {
+    option::Option::{self, None, Some}
+}
+
+// branches on a `Fallibility` type parameter. the condition is a constant, so only one branch is
+//  compiled in
+macro_rules! fallible_q {
+    ($m:ident, $f:expr, $i:expr) => {
+        if $m::FALLIBLE { $f } else { $i }
+    };
+}
+
 pub mod args;
+pub mod checked_args;
+pub(crate) mod contiguous;
+pub mod env;
+pub mod indexed_args;
 pub mod mapped_args;
+pub mod sized_args;
 
 /// Helper to get the unsigned remaining distance between two pointers.
 ///
@@ -14,3 +31,40 @@ pub mod mapped_args;
 pub unsafe fn len(cur: *const *const u8, end: *const *const u8) -> usize {
     end.offset_from(cur) as usize
 }
+
+/// Gets the slot of index 0 for an iterator at `cur`: the start of argv if `cur` is in it, so that
+/// indices are positions in argv however far the iterator was advanced, or `cur` itself for
+/// arguments from elsewhere.
+#[allow(clippy::must_use_candidate)]
+#[inline]
+pub(crate) fn argv_base(cur: *const *const u8) -> *const *const u8 {
+    let (argc, argv) = crate::direct::argc_argv();
+    // SAFETY: `argv` has `argc` slots, so one past them is in bounds
+    if !argv.is_null() && cur >= argv && cur <= unsafe { argv.add(argc as usize) } {
+        argv
+    } else {
+        cur
+    }
+}
+
+/// Finds the first `--` argument in `cur..end`.
+///
+/// # Safety
+///
+/// `cur..end` must be a range of valid pointers to null-terminated strings.
+#[allow(clippy::must_use_candidate)]
+#[inline]
+pub(crate) unsafe fn find_double_dash(
+    mut cur: *const *const u8,
+    end: *const *const u8
+) -> Option<*const *const u8> {
+    while cur != end {
+        let p = cur.read();
+        // each byte is only read if the ones before it weren't the terminator
+        if *p == b'-' && *p.add(1) == b'-' && *p.add(2) == 0 {
+            return Some(cur);
+        }
+        cur = cur.add(1);
+    }
+    None
+}
@@ -0,0 +1,130 @@
+import! {
+    {
+        convert::From,
+        iter::{ExactSizeIterator, FusedIterator, Iterator},
+        ops::FnMut,
+        option::Option
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+import! {
+    iter::DoubleEndedIterator
+}
+
+use crate::{Arg, Args, CStr};
+
+/// An iterator over program arguments as <code>[Arg]<'static></code>, which carry their length.
+///
+/// Created by [`Args::sized`]. Each argument's length is found once, as it is yielded.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SizedArgs {
+    inner: Args
+}
+
+#[allow(clippy::inline_always)]
+#[inline(always)]
+fn sized(c: CStr<'static>) -> Arg<'static> {
+    Arg::from(c)
+}
+
+impl Args {
+    /// Turns this iterator into one over <code>[Arg]<'static></code>, which computes each
+    /// argument's length once so later conversions don't have to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let argv = snailx::argv!["prog", "input.txt"];
+    /// // SAFETY: `argv!` only produces static, null-terminated strings
+    /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+    ///
+    /// let lens: Vec<usize> = args.sized().map(|a| a.len()).collect();
+    /// assert_eq!(lens, [4, 9]);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn sized(self) -> SizedArgs {
+        SizedArgs { inner: self }
+    }
+}
+
+impl SizedArgs {
+    /// Gets the element at index `i`, or `None` if the index is out-of-bounds. This does _not_
+    /// consume elements like `nth`.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<Arg<'static>> {
+        self.inner.get(i).map(sized)
+    }
+
+    /// Gets the remaining arguments as an [`Args`], which doesn't compute lengths.
+    #[must_use]
+    #[inline]
+    pub fn unsized_args(self) -> Args {
+        self.inner
+    }
+}
+
+impl Iterator for SizedArgs {
+    type Item = Arg<'static>;
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<Arg<'static>> {
+        self.inner.next().map(sized)
+    }
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn last(self) -> Option<Arg<'static>> {
+        self.inner.last().map(sized)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Arg<'static>> {
+        self.inner.nth(n).map(sized)
+    }
+
+    #[inline]
+    fn fold<B, F: FnMut(B, Arg<'static>) -> B>(self, acc: B, mut f: F) -> B {
+        self.inner.fold(acc, |acc, c| f(acc, sized(c)))
+    }
+}
+
+#[cfg(feature = "rev_iter")]
+impl DoubleEndedIterator for SizedArgs {
+    #[inline]
+    fn next_back(&mut self) -> Option<Arg<'static>> {
+        self.inner.next_back().map(sized)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Arg<'static>> {
+        self.inner.nth_back(n).map(sized)
+    }
+
+    #[inline]
+    fn rfold<B, F: FnMut(B, Arg<'static>) -> B>(self, acc: B, mut f: F) -> B {
+        self.inner.rfold(acc, |acc, c| f(acc, sized(c)))
+    }
+}
+
+impl ExactSizeIterator for SizedArgs {
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl FusedIterator for SizedArgs {}
@@ -2,13 +2,33 @@
 //!
 //! This crate exposes lightweight, zero-copy iterators over program arguments:
 //! - [`Args::new`](Args::new) yields <code>[CStr]<'static></code>
+//! - [`Args::sized`] yields <code>[Arg]<'static></code>, which caches each argument's length
+//! - [`ByContent`] compares, orders and hashes `CStr`s and `Arg`s by contents, e.g. for map keys
 //! - [`MappedArgs::utf8`] yields `&'static str`
-//!   - if the `assume_valid_str` feature is enabled, all arguments are assumed to be valid UTF-8
+//!   - if the deprecated `assume_valid_str` feature is enabled, all arguments are assumed to be
+//!     valid UTF-8
 //!   - if the `assume_valid_str` feature is disabled, invalid UTF-8 arguments are skipped
+//! - [`Args::without_prog`] and [`Args::split_at_double_dash`] skip the program name and split the
+//!   arguments at `--`, without allocating
 //! - [`direct::argc_argv`] returns the raw `(argc, argv)`
 //! - [`MappedArgs::new`] lets you map each `*const u8` argument pointer into a custom type; `None`
 //!   values are skipped
+//! - [`MappedArgs::new_indexed`] does the same, also passing each argument's argv index, and
+//!   [`MappedArgs::indexed`] pairs items with their indices, counting skipped arguments
+//! - [`MappedArgs::utf8_unchecked`] yields `&'static str` without validation, for arguments known
+//!   to be valid UTF-8
+//! - [`MappedArgs::utf8_checked`] yields `Result<&'static str, InvalidArg>`, reporting invalid
+//!   arguments with their index instead of skipping them
+//! - [`MappedArgs::utf8_lossy`] yields each argument as [`Utf8Chunks`], keeping invalid ones
+//! - [`MappedArgs::bytes`] yields `&'static [u8]`, without validation
 //! - [`MappedArgs::osstr`] (with the `std` feature) yields `&'static std::ffi::OsStr`
+//! - [`Env::new`] yields the environment as `(&'static [u8], CStr<'static>)` key/value pairs, and
+//!   [`MappedEnv`] provides the same adapters as [`MappedArgs`]
+//! - [`prog_name`] returns the basename of `argv[0]`, and [`exe_path`] the path of the executable
+//! - [`cstr!`] and [`argv!`] build static, null-terminated strings and fake `argv` arrays
+//! - [`Args::expand_response_files`] (with the `alloc` feature) expands `@file` arguments
+//! - [`on_startup!`] registers functions to run before `main`, with the arguments already available
+//! - [`testing`] (with the `testing` feature) provides guards for faking `argv` in tests
 //!
 //! `no_std` by default; enable the `std` feature for `OsStr` support.
 //! Targets Unix-like systems and macOS.
@@ -22,11 +42,21 @@
 #![allow(clippy::use_self, clippy::similar_names, clippy::cast_lossless, clippy::doc_markdown)]
 
 #[cfg(feature = "alloc")] extern crate alloc;
-extern crate core;
+// `no_std` already puts `core` in the crate root, so declaring it again there is E0259
+#[cfg(feature = "std")] extern crate core;
 
 #[cfg(not(any(unix, target_vendor = "apple")))]
 compile_error!("snailx only supports Unix and macOS");
 
+// `assume_valid_str` is unified across the whole build, so a binary can use this to make sure no
+//  dependency turned it on
+#[cfg(all(feature = "assume_valid_str", feature = "forbid_assume_valid_str"))]
+compile_error!(
+    "the deprecated `assume_valid_str` feature of snailx was enabled by a crate in the build, but \
+     `forbid_assume_valid_str` disallows it. use `MappedArgs::utf8_unchecked` or \
+     `IndexingParser::set_assume_utf8` instead"
+);
+
 macro_rules! import {
     ($($v:tt)*) => {
         #[cfg(feature = "std")]
@@ -52,11 +82,16 @@ macro_rules! switch {
 }
 
 macro_rules! assume {
+    // with the `checked` feature, every arm fails with a panic instead of causing UB
+
     // completely unreachable branches
     // assumes expression is absolutely false
     (!$e:expr) => {
         if $e {
+            #[cfg(feature = "checked")]
+            panic!(concat!("assumption failed: !(", stringify!($e), ")"));
             // SAFETY: this is unreachable
+            #[cfg(not(feature = "checked"))]
             #[allow(unused_unsafe)]
             unsafe {
                 switch!(core::hint::unreachable_unchecked(););
@@ -64,10 +99,23 @@ macro_rules! assume {
         }
     };
 
+    // returns `$ret` with `checked` if expression is true, otherwise assumes it is false
+    (or $ret:expr, $e:expr) => {
+        #[cfg(feature = "checked")]
+        if $e {
+            return $ret;
+        }
+        #[cfg(not(feature = "checked"))]
+        assume!(!$e);
+    };
+
     // assumes expression is absolutely true
     ($e:expr) => {
         if !$e {
+            #[cfg(feature = "checked")]
+            panic!(concat!("assumption failed: ", stringify!($e)));
             // SAFETY: this is unreachable
+            #[cfg(not(feature = "checked"))]
             #[allow(unused_unsafe)]
             unsafe {
                 switch!(core::hint::unreachable_unchecked(););
@@ -75,9 +123,9 @@ macro_rules! assume {
         }
     };
 
-    // debug-only check with custom message
+    // debug-only check with custom message (also checked in release with `checked`)
     (dbg, $e:expr, $($msg:tt)+) => {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, feature = "checked"))]
         if !$e {
             panic!($($msg)+);
         }
@@ -87,6 +135,9 @@ macro_rules! assume {
     (car, $exp:ident, $in_name:ident, $e:expr, $($msg:tt)+) => {
         match $e {
             $exp($in_name) => $in_name,
+            #[cfg(feature = "checked")]
+            _ => panic!($($msg)+),
+            #[cfg(not(feature = "checked"))]
             #[allow(unused_unsafe)]
             _ => unsafe { switch!(core::hint::unreachable_unchecked();) },
         }
@@ -95,12 +146,12 @@ macro_rules! assume {
     // custom message for both debug and release (similar to debug_assert)
     ($e:expr, $($msg:tt)+) => {
         if !$e {
-            #[cfg(debug_assertions)]
+            #[cfg(any(debug_assertions, feature = "checked"))]
             {
                 panic!($($msg)+);
             }
             // SAFETY: guarded in debug by the above, UB in release builds if used improperly
-            #[cfg(not(debug_assertions))]
+            #[cfg(not(any(debug_assertions, feature = "checked")))]
             #[allow(unused_unsafe)]
             unsafe {
                 switch!(core::hint::unreachable_unchecked(););
@@ -109,29 +160,65 @@ macro_rules! assume {
     };
 }
 
+mod arg;
+mod chunks;
+mod content;
 pub mod direct;
+mod display;
 mod ffi;
+mod macros;
 
 mod iter;
+mod prog;
+#[cfg(feature = "alloc")] mod response;
+#[cfg(any(feature = "rust_strlen", feature = "__bench"))] mod strlen;
 
 #[cfg(any(feature = "indexing_parser", feature = "non_indexing_parser"))] mod parser;
 
 #[cfg(feature = "indexing_parser")] pub use parser::indexing as indexing_parser;
 #[cfg(feature = "non_indexing_parser")] pub use parser::non_indexing as non_indexing_parser;
 
-#[cfg(any(debug_assertions, not(feature = "assume_valid_str")))] mod str_checks;
+mod str_checks;
+
+#[cfg(feature = "testing")] pub mod testing;
 
 #[cfg(any(feature = "to_core_cstr", feature = "std"))] pub use ffi::minimal_cstr::StdCStr;
+#[cfg(feature = "alloc")]
+pub use response::{RESPONSE_FILE_DEPTH, ResponseFileError, ResponseFileErrorKind};
 pub use {
+    arg::Arg,
+    chunks::{Utf8Chunk, Utf8Chunks},
+    content::ByContent,
+    display::{EscapeDebug, LossyDisplay},
     ffi::minimal_cstr::CStr,
-    iter::{args::*, mapped_args::*}
+    iter::{args::*, checked_args::*, env::*, indexed_args::*, mapped_args::*, sized_args::*},
+    prog::{exe_path, is_login_shell, prog_name, prog_name_str}
 };
 
+// used by the exported macros
+#[doc(hidden)]
+pub mod __private {
+    pub use macros::{count, has_nul, run_hook};
+}
+
 #[cfg(feature = "__bench")]
 #[allow(missing_docs)]
 #[doc(hidden)]
 pub mod bench_helpers {
-    pub use {ffi::strlen, helpers::*, iter::len};
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub use direct::procfs::split_cmdline;
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    pub use str_checks::x86_64::{
+        avx2::is_valid as is_valid_utf8_avx2,
+        sse41::is_valid as is_valid_utf8_sse41
+    };
+    pub use {
+        ffi::strlen,
+        helpers::*,
+        iter::{contiguous::checked_contiguous, len},
+        str_checks::{is_valid_utf8, validate_utf8_scalar},
+        strlen::strlen_bytes
+    };
 }
 
 mod helpers {
@@ -154,7 +241,7 @@ mod helpers {
     pub fn try_to_str(p: *const u8) -> Option<&'static str> {
         // SAFETY: only called internally with valid CStr pointers from argv
         unsafe {
-            assume!(!p.is_null());
+            assume!(or switch!(core::option::Option::None), p.is_null());
             let len = strlen(p.cast());
             let bytes = slice::from_raw_parts(p, len + 1);
             assume!(
@@ -183,6 +270,56 @@ mod helpers {
         }
     }
 
+    // `try_to_str` for arguments already known, or promised by the caller, to be valid UTF-8
+    #[inline]
+    #[allow(
+        clippy::must_use_candidate,
+        clippy::not_unsafe_ptr_arg_deref,
+        clippy::transmute_bytes_to_str,
+        clippy::unnecessary_wraps,
+        missing_docs
+    )]
+    pub fn to_str_unchecked(p: *const u8) -> Option<&'static str> {
+        // SAFETY: only called internally with valid CStr pointers from a validated argv block, or
+        //  from an `unsafe` constructor whose caller guarantees validity
+        unsafe {
+            assume!(or switch!(core::option::Option::None), p.is_null());
+            let str_bytes = slice::from_raw_parts(p, strlen(p));
+            assume!(
+                dbg,
+                crate::str_checks::is_valid_utf8(str_bytes),
+                "invalid UTF-8 in CStr during unchecked conversion to str"
+            );
+            Some(transmute::<&'static [u8], &'static str>(str_bytes))
+        }
+    }
+
+    #[inline]
+    #[allow(
+        clippy::unnecessary_wraps,
+        clippy::must_use_candidate,
+        clippy::not_unsafe_ptr_arg_deref,
+        missing_docs
+    )]
+    pub fn to_bytes(p: *const u8) -> Option<&'static [u8]> {
+        // SAFETY: only called internally with valid CStr pointers from argv
+        unsafe {
+            assume!(or switch!(core::option::Option::None), p.is_null());
+            Some(slice::from_raw_parts(p, strlen(p)))
+        }
+    }
+
+    #[inline]
+    #[allow(
+        clippy::unnecessary_wraps,
+        clippy::must_use_candidate,
+        clippy::not_unsafe_ptr_arg_deref,
+        missing_docs
+    )]
+    pub fn to_utf8_chunks(p: *const u8) -> Option<crate::Utf8Chunks<'static>> {
+        Some(crate::Utf8Chunks::new(to_bytes(p)?))
+    }
+
     #[cfg(feature = "std")]
     #[inline]
     #[allow(
@@ -194,13 +331,83 @@ mod helpers {
     pub fn to_osstr(p: *const u8) -> Option<&'static ::std::ffi::OsStr> {
         // SAFETY: only called internally with valid CStr pointers from argv
         unsafe {
-            assume!(!p.is_null());
+            assume!(or switch!(core::option::Option::None), p.is_null());
             let len = strlen(p.cast());
-            assume!(!len == 0);
             Some(&*(switch!(core::ptr::slice_from_raw_parts(p, len)) as *const ::std::ffi::OsStr))
         }
     }
 
+    /// Splits a `KEY=value` environment entry at the first `=`.
+    ///
+    /// # Safety
+    ///
+    /// `p` must be a valid pointer to a null-terminated string.
+    #[inline]
+    #[allow(clippy::must_use_candidate, missing_docs)]
+    pub unsafe fn split_env(p: *const u8) -> (&'static [u8], crate::CStr<'static>) {
+        assume!(!p.is_null());
+        let len = strlen(p.cast());
+        let bytes = slice::from_raw_parts(p, len);
+
+        let mut eq = 0;
+        while eq < len && bytes[eq] != b'=' {
+            eq += 1;
+        }
+
+        // if there is no `=`, the value is the empty string at the null terminator
+        let val = if eq == len { p.add(len) } else { p.add(eq + 1) };
+        (slice::from_raw_parts(p, eq), crate::CStr::from_ptr(val))
+    }
+
+    #[inline]
+    #[allow(
+        clippy::must_use_candidate,
+        clippy::not_unsafe_ptr_arg_deref,
+        clippy::transmute_bytes_to_str,
+        clippy::type_complexity,
+        missing_docs
+    )]
+    pub fn env_to_str(p: *const u8) -> Option<(&'static str, &'static str)> {
+        // SAFETY: only called internally with valid CStr pointers from envp
+        let (k, v) = unsafe { split_env(p) };
+
+        #[cfg(not(feature = "assume_valid_str"))]
+        if !crate::str_checks::is_valid_utf8(k) {
+            return switch!(core::option::Option::None);
+        }
+        #[cfg(feature = "assume_valid_str")]
+        assume!(
+            dbg,
+            crate::str_checks::is_valid_utf8(k),
+            "invalid UTF-8 in environment key during conversion to str"
+        );
+
+        // SAFETY: checked above (or assumed with `assume_valid_str`)
+        Some((unsafe { transmute::<&'static [u8], &'static str>(k) }, try_to_str(v.as_ptr())?))
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    #[allow(
+        clippy::unnecessary_wraps,
+        clippy::must_use_candidate,
+        clippy::not_unsafe_ptr_arg_deref,
+        clippy::type_complexity,
+        missing_docs,
+        unused_qualifications
+    )]
+    pub fn env_to_osstr(
+        p: *const u8
+    ) -> Option<(&'static ::std::ffi::OsStr, &'static ::std::ffi::OsStr)> {
+        use std::os::unix::ffi::OsStrExt;
+
+        // SAFETY: only called internally with valid CStr pointers from envp
+        let (k, v) = unsafe { split_env(p) };
+        let v = unsafe { slice::from_raw_parts(v.as_ptr(), strlen(v.as_ptr().cast())) };
+
+        Some((::std::ffi::OsStr::from_bytes(k), ::std::ffi::OsStr::from_bytes(v)))
+    }
+
     #[cfg(any(feature = "std", feature = "to_core_cstr"))]
     #[inline]
     #[allow(clippy::must_use_candidate, clippy::not_unsafe_ptr_arg_deref, missing_docs)]
@@ -217,6 +424,21 @@ mod helpers {
     #[inline(always)]
     #[cfg_attr(not(feature = "no_cold"), cold)]
     pub fn back(argv: *const *const u8, argc: u32) -> *const *const u8 {
+        // with `checked`, a null argv has no arguments, and they end at the first null entry
+        #[cfg(feature = "checked")]
+        if argv.is_null() {
+            return argv;
+        }
+        #[cfg(feature = "checked")]
+        let argc = {
+            let mut n = 0;
+            // SAFETY: argv points to at least argc pointers
+            while n < argc && unsafe { !argv.add(n as usize).read().is_null() } {
+                n += 1;
+            }
+            n
+        };
+
         assume!(!argv.is_null(), "`back`: argv is null");
         // SAFETY: argv points to a valid slice of argc count pointers, this is one past the last
         // but always decremented before deref
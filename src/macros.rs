@@ -0,0 +1,152 @@
+// public macros. these use only `macro_rules!` and 1.46-era `const fn`s so they work on the MSRV.
+
+/// Creates a <code>[CStr](crate::CStr)<'static></code> from a string literal, appending the null
+/// terminator.
+///
+/// Fails to compile if the literal contains an interior null byte.
+///
+/// # Examples
+///
+/// ```
+/// let s = snailx::cstr!("hello");
+/// assert_eq!(s.len(), 5);
+///
+/// // also usable in constants
+/// const FLAG: snailx::CStr<'static> = snailx::cstr!("--flag");
+/// assert_eq!(FLAG.len(), 6);
+/// ```
+///
+/// ```compile_fail
+/// let s = snailx::cstr!("inter\0ior");
+/// ```
+#[macro_export]
+macro_rules! cstr {
+    ($s:literal) => {{
+        // an array length mismatch here means the literal contains a null byte
+        const _NO_INTERIOR_NUL: [(); 0] = [(); $crate::__private::has_nul($s) as usize];
+        // SAFETY: the literal is null-terminated and contains no interior null bytes, as checked
+        //  above
+        #[allow(unused_unsafe)]
+        unsafe {
+            $crate::CStr::from_bytes_with_nul_unchecked(concat!($s, "\0").as_bytes())
+        }
+    }};
+}
+
+/// Creates a `&'static [*const u8; N]` of null-terminated strings from string literals, suitable
+/// for [`direct::set_argc_argv`](crate::direct::set_argc_argv) or
+/// [`Args::from_raw`](crate::Args::from_raw).
+///
+/// Each literal is checked for interior null bytes like [`cstr!`].
+///
+/// # Examples
+///
+/// ```
+/// let argv = snailx::argv!["prog", "--flag", "v"];
+/// // SAFETY: every pointer comes from `argv!`, so is static and null-terminated
+/// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+///
+/// assert_eq!(args.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! argv {
+    ($($s:literal),* $(,)?) => {{
+        const ARGV: [*const u8; $crate::__private::count(&[$($s),*])] =
+            [$($crate::cstr!($s).as_ptr()),*];
+        &ARGV
+    }};
+}
+
+/// Registers functions to run before `main`, after `snailx`'s own startup hook, so [`Args`],
+/// [`Env`] and everything in [`direct`] already work inside them.
+///
+/// Each function is placed in `.init_array.00099` on ELF targets, right after `snailx`'s
+/// `.init_array.00098` hook, and in `__DATA,__mod_init_func` on Apple targets. The order between
+/// functions registered this way is unspecified (in practice, it's link order). Works without
+/// `std`.
+///
+/// The functions run before `main` and before the standard library is guaranteed to be fully set
+/// up, so should stick to simple work. They must not panic. With `snailx`'s `std` feature, a panic
+/// is caught and aborts the process. Without it, unwinding out of a hook reaches the `extern "C"`
+/// function calling it, which is undefined behavior on older compilers, including the MSRV; build
+/// with `panic = "abort"` if a hook might panic.
+///
+/// [`Args`]: crate::Args
+/// [`Env`]: crate::Env
+/// [`direct`]: crate::direct
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// static TRACE: AtomicBool = AtomicBool::new(false);
+///
+/// snailx::on_startup! {
+///     fn enable_tracing() {
+///         if snailx::MappedArgs::utf8().any(|a| a == "--trace-startup") {
+///             TRACE.store(true, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// // doctests aren't run with `--trace-startup`
+/// assert!(!TRACE.load(Ordering::Relaxed));
+/// ```
+#[macro_export]
+macro_rules! on_startup {
+    ($($(#[$attr:meta])* $vis:vis fn $name:ident() $body:block)*) => {
+        $(
+            $(#[$attr])*
+            $vis fn $name() $body
+
+            const _: () = {
+                #[used]
+                #[cfg_attr(
+                    not(target_vendor = "apple"),
+                    link_section = ".init_array.00099"
+                )]
+                #[cfg_attr(target_vendor = "apple", link_section = "__DATA,__mod_init_func")]
+                static HOOK: extern "C" fn() = {
+                    extern "C" fn hook() {
+                        $crate::__private::run_hook($name);
+                    }
+                    hook
+                };
+            };
+        )*
+    };
+}
+
+/// Runs an [`on_startup!`] hook. With `std`, a panic aborts the process instead of unwinding into
+/// the `extern "C"` function calling this.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub fn run_hook(f: fn()) {
+    #[cfg(feature = "std")]
+    if ::std::panic::catch_unwind(f).is_err() {
+        ::std::process::abort();
+    }
+    #[cfg(not(feature = "std"))]
+    f();
+}
+
+/// Returns whether `s` contains a null byte.
+#[must_use]
+pub const fn has_nul(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == 0 {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Returns the number of strings in `strs`.
+#[must_use]
+pub const fn count(strs: &[&str]) -> usize {
+    strs.len()
+}
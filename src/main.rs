@@ -47,25 +47,25 @@ where
     );
 }
 
-const ARGS: [*const u8; 18] = [
-    "target/debug/snailx\0".as_ptr(),
-    "hi\0".as_ptr(),
-    "1\0".as_ptr(),
-    "2\0".as_ptr(),
-    "3\0".as_ptr(),
-    "4\0".as_ptr(),
-    "5\0".as_ptr(),
-    "6\0".as_ptr(),
-    "7\0".as_ptr(),
-    "8\0".as_ptr(),
-    "9\0".as_ptr(),
-    "10\0".as_ptr(),
-    "11\0".as_ptr(),
-    "12\0".as_ptr(),
-    "13\0".as_ptr(),
-    "14\0".as_ptr(),
-    "15\0".as_ptr(),
-    "-g\0".as_ptr()
+const ARGS: &[*const u8; 18] = snailx::argv![
+    "target/debug/snailx",
+    "hi",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "10",
+    "11",
+    "12",
+    "13",
+    "14",
+    "15",
+    "-g"
 ];
 
 #[cfg(any(feature = "std", feature = "to_core_cstr"))]
@@ -93,9 +93,9 @@ fn main() {
 
     println!(
         "a: \"{}\", b: \"{}\", c: \"{}\" d: \"{}\"",
-        a.unwrap().to_stdlib().to_string_lossy(),
+        a.unwrap().display(),
         b.unwrap(),
-        c.unwrap().to_stdlib().to_string_lossy(),
+        c.unwrap().display(),
         d.unwrap()
     );
 
@@ -112,16 +112,16 @@ fn main() {
 
         println!(
             "a: \"{}\", b: \"{}\", c: \"{}\" d: \"{}\"",
-            a.unwrap().to_stdlib().to_string_lossy(),
+            a.unwrap().display(),
             b.unwrap(),
-            c.unwrap().to_stdlib().to_string_lossy(),
+            c.unwrap().display(),
             d.unwrap()
         );
     }
 
     // print args for testing
     for arg in Args::new() {
-        println!("cstr arg: {}", arg.to_stdlib().to_string_lossy());
+        println!("cstr arg: {}", arg.display());
     }
 
     println!();
@@ -142,7 +142,7 @@ fn main() {
         println!("\nReversed:");
 
         for arg in Args::new().rev() {
-            println!("rev cstr arg: {}", arg.to_stdlib().to_string_lossy());
+            println!("rev cstr arg: {}", arg.display());
         }
     }
 
@@ -178,7 +178,7 @@ fn main() {
         println!("Parsed pretty: {:#?}\n", args);
 
         #[allow(clippy::items_after_statements)]
-        const NUM: [*const u8; 1] = ["-n\0".as_ptr()];
+        const NUM: &[*const u8; 1] = snailx::argv!["-n"];
 
         args.reset();
         unsafe {
@@ -190,7 +190,7 @@ fn main() {
         println!("Parsed pretty (incomplete n): {:#?}\n", args);
 
         #[allow(clippy::items_after_statements)]
-        const NUM_FULL: [*const u8; 2] = ["-n\0".as_ptr(), "10\0".as_ptr()];
+        const NUM_FULL: &[*const u8; 2] = snailx::argv!["-n", "10"];
 
         args.reset();
         unsafe {
@@ -202,7 +202,7 @@ fn main() {
         println!("Parsed pretty (full n): {:#?}\n", args);
 
         #[allow(clippy::items_after_statements)]
-        const NUM_FULL_EQ: [*const u8; 1] = ["--number=10\0".as_ptr()];
+        const NUM_FULL_EQ: &[*const u8; 1] = snailx::argv!["--number=10"];
 
         args.reset();
         unsafe {
@@ -216,7 +216,7 @@ fn main() {
         assert_eq!(args.option("number").map(|mut it| it.next()), Ok(Some("10")));
 
         #[allow(clippy::items_after_statements)]
-        const NUM_FULL_SHORT_SINGLE: [*const u8; 1] = ["-n10\0".as_ptr()];
+        const NUM_FULL_SHORT_SINGLE: &[*const u8; 1] = snailx::argv!["-n10"];
 
         args.reset();
         unsafe {
@@ -230,7 +230,7 @@ fn main() {
         assert_eq!(args.option("number").map(|mut it| it.next()), Ok(Some("10")));
 
         #[allow(clippy::items_after_statements)]
-        const NUM_FULL_SHORT_SINGLE_BUNDLE: [*const u8; 1] = ["-gn10\0".as_ptr()];
+        const NUM_FULL_SHORT_SINGLE_BUNDLE: &[*const u8; 1] = snailx::argv!["-gn10"];
 
         args.reset();
         unsafe {
@@ -1,5 +1,14 @@
 use {
-    crate::{CStr, direct::argc_argv, helpers::try_to_str, iter::len},
+    crate::{
+        Arg,
+        Args,
+        CStr,
+        ResponseFileError,
+        exe_path,
+        helpers::{to_str_unchecked, try_to_str},
+        iter::len,
+        prog::basename
+    },
     alloc::vec::Vec,
     std::{
         clone::Clone,
@@ -15,7 +24,9 @@ use {
         ptr::{self, null},
         result::Result::{self, Err, Ok},
         slice,
-        str::Utf8Error
+        str::Utf8Error,
+        write,
+        writeln
     }
 };
 
@@ -71,7 +82,11 @@ pub struct IndexingParser {
     // the values of positionals. elem 0 = first positional, elem 1 = second, etc.
     positionals: Vec<&'static str>,
     // map correlating the names of named positionals to their indexes.
-    positional_names: HashMap<&'static str, usize>
+    positional_names: HashMap<&'static str, usize>,
+    // whether to expand `@file` arguments before parsing
+    expand_response_files: bool,
+    // whether to skip UTF-8 validation of arguments and values
+    assume_utf8: bool
 }
 
 impl IndexingParser {
@@ -86,10 +101,34 @@ impl IndexingParser {
             prog: EMPTY_STR,
             option_index: BTreeMap::new(),
             positionals: Vec::new(),
-            positional_names: HashMap::new()
+            positional_names: HashMap::new(),
+            expand_response_files: false,
+            assume_utf8: false
         }
     }
 
+    /// Sets whether `@path` response files are expanded before parsing, as with
+    /// [`Args::expand_response_files`]. Off by default.
+    ///
+    /// The expanded arguments are leaked, so this is best left on for a single parse.
+    #[inline]
+    pub fn set_expand_response_files(&mut self, expand: bool) -> &mut IndexingParser {
+        self.expand_response_files = expand;
+        self
+    }
+
+    /// Sets whether arguments and option values are assumed to be valid UTF-8 instead of checked,
+    /// like [`MappedArgs::utf8_unchecked`](crate::MappedArgs::utf8_unchecked). Off by default.
+    ///
+    /// # Safety
+    ///
+    /// Every argument parsed while this is on must be valid UTF-8.
+    #[inline]
+    pub unsafe fn set_assume_utf8(&mut self, assume: bool) -> &mut IndexingParser {
+        self.assume_utf8 = assume;
+        self
+    }
+
     /// Clear parsed index and reset parser state.
     pub fn reset(&mut self) {
         self.prog = EMPTY_STR;
@@ -111,11 +150,18 @@ impl IndexingParser {
     /// - `positional_names`: names associated with positional indices, allowing for access via
     ///   [`named_positional`](IndexingParser::named_positional).
     /// - `is_first_prog`: callback that identifies the program executable in the first arg.
+    ///   [`is_exe_name`] is a sensible default.
     /// - `allow_multiple_short_vals`: whether to allow "-nm 100 100" syntax (`true`) or "-n1000"
     ///   (`false`) syntax. For a parser more similar to existing standards, this should be `false`.
     ///
+    /// The arguments are read once, as a consistent `(argc, argv)` pair (see
+    /// [`direct::argc_argv`](crate::direct::argc_argv)), at the start of parsing.
+    ///
     /// # Errors
     ///
+    /// - [`Error::ResponseFile`] if response file expansion is enabled (see
+    ///   [`set_expand_response_files`](IndexingParser::set_expand_response_files)) and a response
+    ///   file couldn't be expanded. Parsing aborted.
     /// - [`Error::InvalidStr`] if any argument contains invalid UTF-8. Parsing aborted.
     /// - [`Error::WrongPositionalCount(n)`] if the number of found positionals was not in
     ///   `positional_range`. Parsing was otherwise successful.
@@ -133,9 +179,38 @@ impl IndexingParser {
             // already parsed
             return Ok(());
         }
-        let (argc, argv) = argc_argv();
+        let (argc, argv) = if self.expand_response_files {
+            match Args::new().expand_response_files() {
+                #[allow(clippy::cast_possible_truncation)]
+                Ok(args) => (args.len() as u32, args.cur),
+                Err(e) => return Err(Error::ResponseFile(e))
+            }
+        } else {
+            #[cfg(not(feature = "checked"))]
+            {
+                crate::direct::argc_argv()
+            }
+            // null entries and a null argv are cut off by `Args`
+            #[cfg(feature = "checked")]
+            {
+                let args = Args::new();
+                #[allow(clippy::cast_possible_truncation)]
+                (args.len() as u32, args.cur)
+            }
+        };
         let len_1 = argc as usize;
-        let len = len_1 - 1;
+
+        let assume_utf8 = self.assume_utf8;
+        // SAFETY: `p` is always from argv, and the caller of `set_assume_utf8` guaranteed validity
+        let to_str = |p: *const u8| -> Result<&'static str, Utf8Error> {
+            unsafe {
+                if assume_utf8 {
+                    Ok(tri!(unrp to_str_unchecked(p)))
+                } else {
+                    CStr::from_ptr(p).to_str()
+                }
+            }
+        };
 
         let mut i = 0;
         let mut end_of_args = false;
@@ -153,22 +228,23 @@ impl IndexingParser {
             .filter(|(_, i)| positional_range.contains(i))
             .collect::<HashMap<_, _>>();
 
+        // with `checked`, there may be no arguments at all
+        #[cfg(feature = "checked")]
+        if len_1 == 0 {
+            return self.finish(&found_required, &positional_range);
+        }
+        let len = len_1 - 1;
+
         unsafe {
             loop {
                 let current_raw = argv.add(i);
                 let current = current_raw.read();
                 // TODO: maybe allow non-UTF8?
-                let str = if let Some(next) = next {
-                    next
-                } else {
-                    tri!(str:i CStr::from_ptr(current).to_stdlib().to_str())
-                };
+                let str = if let Some(next) = next { next } else { tri!(str:i to_str(current)) };
 
                 if i < len {
                     let i = i + 1;
-                    next = Some(
-                        tri!(str:i CStr::from_ptr(current_raw.add(1).read()).to_stdlib().to_str())
-                    );
+                    next = Some(tri!(str:i to_str(current_raw.add(1).read())));
                 }
 
                 if i == 0 && is_first_prog(str) {
@@ -217,20 +293,29 @@ impl IndexingParser {
 
                 i += 1;
                 if i == len_1 {
-                    let missing = found_required
-                        .iter()
-                        .filter_map(|(name, found)| if *found { None } else { Some(*name) });
-                    if missing.clone().count() != 0 {
-                        return Err(Error::MissingRequired(missing.collect()));
-                    } else if !positional_range.contains(&self.positional_count()) {
-                        return Err(Error::WrongPositionalCount(self.positional_count()));
-                    }
-                    return Ok(());
+                    return self.finish(&found_required, &positional_range);
                 }
             }
         }
     }
 
+    // checks for missing required options and the positional count once every argument is parsed
+    fn finish(
+        &self,
+        found_required: &HashMap<&'static str, bool>,
+        positional_range: &impl RangeBounds<usize>
+    ) -> Result<(), Error> {
+        let missing = found_required
+            .iter()
+            .filter_map(|(name, found)| if *found { None } else { Some(*name) });
+        if missing.clone().count() != 0 {
+            return Err(Error::MissingRequired(missing.collect()));
+        } else if !positional_range.contains(&self.positional_count()) {
+            return Err(Error::WrongPositionalCount(self.positional_count()));
+        }
+        Ok(())
+    }
+
     // accessors
 
     /// Returns program name if detected by `is_first_prog` during `parse`.
@@ -307,7 +392,8 @@ impl IndexingParser {
                 return Ok(OptValues {
                     cur: val.cast::<*const u8>(),
                     end: unsafe { val.cast::<*const u8>().add((&*val).len()) },
-                    offset: arg.val_offset()
+                    offset: arg.val_offset(),
+                    assume_utf8: self.assume_utf8
                 });
             }
         }
@@ -467,10 +553,8 @@ impl IndexingParser {
             if i != 0 {
                 write!(f, ", ")?;
             }
-            match try_to_str(unsafe { p.add(val_offset) }) {
-                Some(s) => write!(f, "{:?}", s)?,
-                None => write!(f, "{:p}", p)?
-            }
+            // SAFETY: values are valid cstrs from argv, and the offset is within the first one
+            write!(f, "{:?}", unsafe { CStr::from_ptr(p.add(val_offset)) })?;
         }
         write!(f, "]")
     }
@@ -744,9 +828,34 @@ impl Argument {
     }
 }
 
+/// Returns whether `arg` names the running executable, by comparing its basename with that of
+/// [`exe_path`], ignoring a leading login-shell `-`. Returns `true` if the executable's path is
+/// unavailable.
+///
+/// Intended as the `is_first_prog` callback of [`IndexingParser::parse`].
+#[must_use]
+pub fn is_exe_name(arg: &'static str) -> bool {
+    let name = match arg.rfind('/') {
+        Some(i) => &arg.as_bytes()[i + 1..],
+        None => arg.as_bytes()
+    };
+    let name = match name.split_first() {
+        Some((&b'-', rest)) => rest,
+        _ => name
+    };
+
+    match exe_path() {
+        // SAFETY: `exe_path` only returns valid, null-terminated strings
+        Some(p) => unsafe { CStr::from_ptr(basename(p.as_ptr())) == *name },
+        None => true
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 /// An error which can occur while parsing arguments.
 pub enum Error {
+    /// A response file couldn't be expanded.
+    ResponseFile(ResponseFileError),
     /// An argument contained invalid UTF-8.
     InvalidStr(usize, Utf8Error),
     /// Parsing was successful, but by the end there were too few or too many positionals.
@@ -762,10 +871,13 @@ pub enum Error {
 }
 
 /// An iterator over the values of an option.
+#[derive(Clone, Debug)]
 pub struct OptValues {
     cur: *const *const u8,
     end: *const *const u8,
-    offset: usize
+    offset: usize,
+    // from the parser's `set_assume_utf8`
+    assume_utf8: bool
 }
 
 impl OptValues {
@@ -790,6 +902,69 @@ impl OptValues {
     }
 }
 
+impl OptValues {
+    /// Turns this iterator into one over <code>[Arg]<'static></code>, which computes each value's
+    /// length once so later conversions don't have to. Unlike this iterator, values which aren't
+    /// valid UTF-8 are yielded too.
+    #[must_use]
+    #[inline]
+    pub fn sized(self) -> SizedOptValues {
+        SizedOptValues { inner: self }
+    }
+}
+
+/// An iterator over the values of an option as <code>[Arg]<'static></code>. Created by
+/// [`OptValues::sized`].
+#[derive(Clone, Debug)]
+pub struct SizedOptValues {
+    inner: OptValues
+}
+
+impl SizedOptValues {
+    /// Gets the element at index `i`, or `None` if the index is out-of-bounds. This does _not_
+    /// consume elements like `nth`.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<Arg<'static>> {
+        if self.len() > i {
+            // SAFETY: just checked that `i` is in bounds, and values are valid cstrs from argv
+            Some(unsafe { Arg::from_ptr(self.inner.cur.add(i).read().add(self.inner.offset)) })
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for SizedOptValues {
+    type Item = Arg<'static>;
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn next(&mut self) -> Option<Arg<'static>> {
+        let v = &mut self.inner;
+        if v.cur == v.end {
+            return None;
+        }
+        let p = v.cur;
+        v.cur = unsafe { v.cur.add(1) };
+
+        // SAFETY: `p` is in bounds, and values are valid cstrs from argv
+        Some(unsafe { Arg::from_ptr(p.read().add(v.offset)) })
+    }
+
+    #[allow(clippy::inline_always)]
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for SizedOptValues {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
 impl Iterator for OptValues {
     type Item = &'static str;
 
@@ -802,7 +977,8 @@ impl Iterator for OptValues {
         let p = self.cur;
         self.cur = unsafe { self.cur.add(1) };
 
-        try_to_str(unsafe { p.read().add(self.offset) })
+        let p = unsafe { p.read().add(self.offset) };
+        if self.assume_utf8 { to_str_unchecked(p) } else { try_to_str(p) }
     }
 
     #[allow(clippy::inline_always)]
@@ -0,0 +1,147 @@
+// program name helpers built on argv[0] and, on linux, the auxiliary vector and /proc/self/exe.
+
+import! {
+    {
+        iter::Iterator,
+        option::Option::{self, None, Some},
+        slice
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::direct::procfs::{Once, StaticBuf, read_link};
+use crate::{CStr, direct, ffi::strlen, helpers::try_to_str};
+
+/// Gets `argv[0]`, if there is one.
+#[inline]
+fn arg0() -> Option<*const u8> {
+    let (argc, argv) = direct::argc_argv();
+    if argc == 0 || argv.is_null() {
+        return None;
+    }
+    // SAFETY: argv points to at least argc pointers
+    let p = unsafe { argv.read() };
+    assume!(or None, p.is_null());
+    Some(p)
+}
+
+/// Returns a pointer to the part of the null-terminated string at `p` after its last `/`.
+#[inline]
+pub(crate) unsafe fn basename(p: *const u8) -> *const u8 {
+    let bytes = slice::from_raw_parts(p, strlen(p));
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'/' {
+            start = i + 1;
+        }
+    }
+    p.add(start)
+}
+
+/// Returns the program name: the basename of `argv[0]` (everything after its last `/`), or `None`
+/// if there are no arguments.
+///
+/// The returned string points into `argv[0]`, so no allocation is needed. Note that login shells
+/// are conventionally started with a leading `-` (see [`is_login_shell`]), which is kept.
+///
+/// # Examples
+///
+/// ```
+/// use snailx::{argv, direct::set_argc_argv, prog_name};
+///
+/// let args = argv!["/usr/bin/snail", "--help"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings, and nothing else is reading
+/// //  argv
+/// unsafe { set_argc_argv(args.len() as u32, args.as_ptr()) };
+///
+/// assert_eq!(prog_name().unwrap().len(), 5);
+/// assert_eq!(snailx::prog_name_str(), Some("snail"));
+/// ```
+#[must_use]
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub fn prog_name() -> Option<CStr<'static>> {
+    // SAFETY: argv[0] is a valid, null-terminated string, and so is any suffix of it
+    arg0().map(|p| unsafe { CStr::from_ptr(basename(p)) })
+}
+
+/// Returns the program name like [`prog_name`], as a `&'static str`. Returns `None` if there are
+/// no arguments or the name is not valid UTF-8.
+#[must_use]
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub fn prog_name_str() -> Option<&'static str> {
+    match arg0() {
+        // SAFETY: argv[0] is a valid, null-terminated string
+        Some(p) => try_to_str(unsafe { basename(p) }),
+        None => None
+    }
+}
+
+/// Returns whether the program was started as a login shell, which is conventionally indicated by
+/// a leading `-` in `argv[0]` (e.g. `-bash`).
+#[must_use]
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub fn is_login_shell() -> bool {
+    match arg0() {
+        // SAFETY: argv[0] is null-terminated, so its first byte is readable
+        Some(p) => unsafe { p.read() == b'-' },
+        None => false
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const EXE_CAP: usize = 4096;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static EXE_ONCE: Once = Once::new();
+// +1 so the target is always followed by the null terminator from the zeroed buffer
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static EXE_BUF: StaticBuf<[u8; EXE_CAP + 1]> = StaticBuf::new([0; EXE_CAP + 1]);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+static EXE_LEN: StaticBuf<usize> = StaticBuf::new(0);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cold]
+#[inline(never)]
+fn proc_self_exe() -> Option<CStr<'static>> {
+    EXE_ONCE.call_once(|| {
+        // SAFETY: `call_once` guarantees nothing else touches the buffers until we're done. the
+        //  last byte is never written, so the path is always null-terminated.
+        unsafe {
+            let buf = slice::from_raw_parts_mut(EXE_BUF.get().cast::<u8>(), EXE_CAP);
+            if let Some(len) = read_link(b"/proc/self/exe\0", buf) {
+                EXE_LEN.get().write(len);
+            }
+        }
+    });
+
+    // SAFETY: the buffers are no longer written after `call_once` returns
+    unsafe {
+        if EXE_LEN.get().read() == 0 { None } else { Some(CStr::from_ptr(EXE_BUF.get().cast())) }
+    }
+}
+
+/// Returns the path of the running executable.
+///
+/// On Linux, this is, in order of preference, the path passed to `execve` (`AT_EXECFN`, see
+/// [`direct::exec_path`](crate::direct::exec_path)), the target of `/proc/self/exe` (read once into
+/// a static buffer), or `argv[0]`. Elsewhere, it is always `argv[0]`. Returns `None` if none are
+/// available.
+///
+/// Unlike the others, `argv[0]` is chosen freely by whoever started the program, and may not be a
+/// path at all.
+#[must_use]
+#[cfg_attr(not(feature = "no_cold"), cold)]
+pub fn exe_path() -> Option<CStr<'static>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        if let Some(p) = direct::exec_path() {
+            return Some(p);
+        }
+        if let Some(p) = proc_self_exe() {
+            return Some(p);
+        }
+    }
+
+    // SAFETY: argv[0] is a valid, null-terminated string
+    arg0().map(|p| unsafe { CStr::from_ptr(p) })
+}
@@ -0,0 +1,341 @@
+// expansion of `@file` response files, as used by gcc, clang and rustc to get around command line
+//  length limits. the expanded strings are leaked so they can be handed out as `'static` like argv.
+
+import! {
+    {
+        fmt::{Display, Formatter, Result as FmtResult},
+        iter::{ExactSizeIterator, Iterator},
+        matches,
+        option::Option::{self, None, Some},
+        result::Result::{self, Err, Ok},
+        slice,
+        write
+    }
+}
+
+use {
+    crate::{
+        Args,
+        ffi::{EINTR, O_RDONLY, close, errno_location, open, read, strlen}
+    },
+    alloc::{boxed::Box, string::String, vec::Vec}
+};
+
+/// The maximum nesting depth of response files expanded by [`Args::expand_response_files`]. A
+/// response file referenced at this depth is an error, which also catches files that reference
+/// themselves.
+pub const RESPONSE_FILE_DEPTH: usize = 16;
+
+/// The reason a response file couldn't be expanded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResponseFileErrorKind {
+    /// The file couldn't be opened or read.
+    Unreadable,
+    /// A quote was opened but never closed. The line is the one the quote was opened on.
+    UnterminatedQuote,
+    /// The file contains a null byte, which can't be part of an argument.
+    InteriorNul,
+    /// The file references another response file nested more than [`RESPONSE_FILE_DEPTH`] deep.
+    TooDeep
+}
+
+/// An error which can occur while expanding response files, naming the offending file and line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseFileError {
+    pub(crate) path: Vec<u8>,
+    // 0 = not tied to a line
+    pub(crate) line: usize,
+    pub(crate) kind: ResponseFileErrorKind
+}
+
+impl ResponseFileError {
+    /// Gets the path of the offending file, as it was written in the `@path` argument.
+    #[must_use]
+    pub fn path(&self) -> &[u8] {
+        &self.path
+    }
+
+    /// Gets the 1-based line of the offending file the error occurred on, or `None` for
+    /// [`ResponseFileErrorKind::Unreadable`].
+    #[must_use]
+    pub fn line(&self) -> Option<usize> {
+        if self.line == 0 { None } else { Some(self.line) }
+    }
+
+    /// Gets the reason the file couldn't be expanded.
+    #[must_use]
+    pub fn kind(&self) -> ResponseFileErrorKind {
+        self.kind
+    }
+}
+
+impl Display for ResponseFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let path = String::from_utf8_lossy(&self.path);
+        match self.line() {
+            Some(line) => write!(f, "{}:{}: ", path, line)?,
+            None => write!(f, "{}: ", path)?
+        }
+        f.write_str(match self.kind {
+            ResponseFileErrorKind::Unreadable => "couldn't read response file",
+            ResponseFileErrorKind::UnterminatedQuote => "unterminated quote",
+            ResponseFileErrorKind::InteriorNul => "null byte in response file",
+            ResponseFileErrorKind::TooDeep => "response files nested too deeply"
+        })
+    }
+}
+
+impl Args {
+    /// Expands response files in the remaining arguments, returning an iterator over the result.
+    ///
+    /// Every argument of the form `@path` is replaced by the arguments read from the file at
+    /// `path`, which is resolved relative to the working directory. The file is split into
+    /// arguments like a shell would:
+    /// - arguments are separated by whitespace, including newlines
+    /// - `'...'` quotes everything literally
+    /// - `"..."` quotes everything except `\"`, `\\`, `\$`, `` \` `` and `\` followed by a newline,
+    ///   which are unescaped
+    /// - outside of quotes, `\` escapes the next byte, and a `#` at the start of an argument starts
+    ///   a comment which runs to the end of the line
+    ///
+    /// Response files may reference others, up to [`RESPONSE_FILE_DEPTH`] deep. A lone `@` is kept
+    /// as-is, as is every other argument, including the program name if it hasn't been consumed
+    /// yet.
+    ///
+    /// If there are no `@path` arguments, this doesn't allocate. Otherwise, the expanded arguments
+    /// are leaked so they live for `'static` like the originals, so this should be called once,
+    /// at startup.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ResponseFileError`] naming the file (and line, if any) if a response file can't
+    /// be read or split.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let path = std::env::temp_dir().join(format!("snailx-doc-{}.rsp", std::process::id()));
+    /// std::fs::write(&path, "--flag 'two words' # comment\n").unwrap();
+    /// let arg = format!("@{}\0", path.display());
+    /// let argv = [snailx::cstr!("prog").as_ptr(), arg.as_ptr(), snailx::cstr!("last").as_ptr()];
+    /// // SAFETY: every pointer is to a null-terminated string that outlives `args`
+    /// let args = unsafe { snailx::Args::from_raw(argv.len() as u32, argv.as_ptr()) };
+    ///
+    /// let args = args.expand_response_files().unwrap();
+    /// let args: Vec<&str> = args.map_utf8().collect();
+    /// assert_eq!(args, ["prog", "--flag", "two words", "last"]);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg_attr(not(feature = "no_cold"), cold)]
+    pub fn expand_response_files(&self) -> Result<Args, ResponseFileError> {
+        // SAFETY: the pointers are from argv, so are valid null-terminated strings
+        if !self.as_slice().iter().any(|a| unsafe { is_response_file(a.as_ptr()) }) {
+            return Ok(Args { cur: self.cur, end: self.end });
+        }
+
+        let mut out = Vec::with_capacity(self.len());
+        for a in self.as_slice() {
+            // SAFETY: as above
+            unsafe { expand(&mut out, a.as_ptr(), 0, None)? };
+        }
+
+        let len = out.len();
+        let ptrs: &'static [*const u8] = Box::leak(out.into_boxed_slice());
+        let cur = ptrs.as_ptr();
+        // SAFETY: one past the end of the leaked slice
+        Ok(Args { cur, end: unsafe { cur.add(len) } })
+    }
+}
+
+// whether the null-terminated string at `p` is `@` followed by at least one byte
+#[inline]
+unsafe fn is_response_file(p: *const u8) -> bool {
+    p.read() == b'@' && p.add(1).read() != 0
+}
+
+// pushes the null-terminated argument at `arg` to `out`, or the arguments in the file it
+//  references. `parent` is the file and line the argument came from, if it came from a file.
+unsafe fn expand(
+    out: &mut Vec<*const u8>,
+    arg: *const u8,
+    depth: usize,
+    parent: Option<(&[u8], usize)>
+) -> Result<(), ResponseFileError> {
+    if !is_response_file(arg) {
+        out.push(arg);
+        return Ok(());
+    }
+
+    if depth == RESPONSE_FILE_DEPTH {
+        return Err(match parent {
+            Some((path, line)) => ResponseFileError {
+                path: path.to_vec(),
+                line,
+                kind: ResponseFileErrorKind::TooDeep
+            },
+            // unreachable as long as RESPONSE_FILE_DEPTH > 0
+            None => ResponseFileError {
+                path: Vec::new(),
+                line: 0,
+                kind: ResponseFileErrorKind::TooDeep
+            }
+        });
+    }
+
+    let path = arg.add(1);
+    let path_bytes = slice::from_raw_parts(path, strlen(path));
+    let err = |line, kind| ResponseFileError { path: path_bytes.to_vec(), line, kind };
+
+    let contents = match read_all(path) {
+        Some(c) => c,
+        None => return Err(err(0, ResponseFileErrorKind::Unreadable))
+    };
+
+    for (word, line) in split(&contents).map_err(|(line, kind)| err(line, kind))? {
+        if is_response_file(word.as_ptr()) {
+            expand(out, word.as_ptr(), depth + 1, Some((path_bytes, line)))?;
+        } else {
+            let word: &'static [u8] = Box::leak(word.into_boxed_slice());
+            out.push(word.as_ptr());
+        }
+    }
+    Ok(())
+}
+
+// reads the whole file at the null-terminated `path`
+unsafe fn read_all(path: *const u8) -> Option<Vec<u8>> {
+    let fd = open(path, O_RDONLY);
+    if fd < 0 {
+        return None;
+    }
+
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        buf.reserve(4096);
+        let spare = buf.capacity() - buf.len();
+        let n = read(fd, buf.as_mut_ptr().add(buf.len()), spare);
+        if n < 0 {
+            // interrupted by a signal before reading anything
+            if errno_location().read() == EINTR {
+                continue;
+            }
+            close(fd);
+            return None;
+        } else if n == 0 {
+            break;
+        }
+        #[allow(clippy::cast_sign_loss)]
+        buf.set_len(buf.len() + n as usize);
+    }
+    close(fd);
+
+    Some(buf)
+}
+
+const fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0B | 0x0C)
+}
+
+// splits `contents` into null-terminated arguments and the lines they start on, or returns the
+//  line and reason it couldn't be split
+#[allow(clippy::type_complexity)]
+fn split(contents: &[u8]) -> Result<Vec<(Vec<u8>, usize)>, (usize, ResponseFileErrorKind)> {
+    let mut words = Vec::new();
+    let mut word = Vec::new();
+    // whether a word has been started, since quotes can produce an empty one
+    let mut in_word = false;
+    let mut word_line = 1;
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < contents.len() {
+        let b = contents[i];
+        i += 1;
+
+        if b == 0 {
+            return Err((line, ResponseFileErrorKind::InteriorNul));
+        }
+        if is_space(b) {
+            if in_word {
+                word.push(0);
+                words.push((word, word_line));
+                word = Vec::new();
+                in_word = false;
+            }
+            if b == b'\n' {
+                line += 1;
+            }
+            continue;
+        }
+        if b == b'#' && !in_word {
+            while i < contents.len() && contents[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        // `\` and a newline are removed entirely, so they don't start an argument
+        if b == b'\\' && contents.get(i) == Some(&b'\n') {
+            i += 1;
+            line += 1;
+            continue;
+        }
+        if !in_word {
+            in_word = true;
+            word_line = line;
+        }
+
+        match b {
+            b'\'' | b'"' => {
+                let start_line = line;
+                loop {
+                    let c = match contents.get(i) {
+                        Some(&c) => c,
+                        None => return Err((start_line, ResponseFileErrorKind::UnterminatedQuote))
+                    };
+                    i += 1;
+
+                    if c == b {
+                        break;
+                    } else if c == 0 {
+                        return Err((line, ResponseFileErrorKind::InteriorNul));
+                    } else if c == b'\n' {
+                        line += 1;
+                    } else if c == b'\\' && b == b'"' {
+                        match contents.get(i) {
+                            Some(&e @ b'"') | Some(&e @ b'\\') | Some(&e @ b'$')
+                            | Some(&e @ b'`') => {
+                                i += 1;
+                                word.push(e);
+                                continue;
+                            }
+                            Some(b'\n') => {
+                                i += 1;
+                                line += 1;
+                                continue;
+                            }
+                            _ => {}
+                        }
+                    }
+                    word.push(c);
+                }
+            }
+            b'\\' => match contents.get(i) {
+                Some(0) => return Err((line, ResponseFileErrorKind::InteriorNul)),
+                Some(&c) => {
+                    i += 1;
+                    word.push(c);
+                }
+                // a trailing backslash is kept
+                None => word.push(b)
+            },
+            _ => word.push(b)
+        }
+    }
+
+    if in_word {
+        word.push(0);
+        words.push((word, word_line));
+    }
+    Ok(words)
+}
@@ -1,15 +1,59 @@
 extern crate core;
 
 import! {
-    mem::size_of
+    {
+        mem::size_of,
+        option::Option::{self, None, Some},
+        result::Result::{self, Err, Ok}
+    }
 }
 
+#[cfg(all(target_arch = "x86_64", not(miri)))] pub mod x86_64;
+
 // TODO: i remember this being faster than just copying the implementation from str/validations.rs.
 //  make sure that is still the case.
 
+/// Checks whether `v` is valid UTF-8, with a vector validator for long inputs where available.
+#[cfg_attr(all(feature = "assume_valid_str", not(debug_assertions)), allow(dead_code))]
 #[allow(clippy::inline_always)]
 #[inline(always)]
 pub fn is_valid_utf8(v: &[u8]) -> bool {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        if v.len() >= x86_64::MIN_LEN {
+            return x86_64::is_valid_utf8(v);
+        }
+    }
+    validate_utf8_scalar(v).is_ok()
+}
+
+// the position and length of the first invalid sequence, like `core::str::Utf8Error`, which can't
+//  be constructed outside of core
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Utf8Error {
+    pub valid_up_to: usize,
+    // `None` if the bytes end in the middle of a sequence
+    pub error_len: Option<u8>
+}
+
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub fn validate_utf8(v: &[u8]) -> Result<(), Utf8Error> {
+    // the vector validators only say whether the bytes are valid, so the scalar one finds where
+    //  they aren't
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        if v.len() >= x86_64::MIN_LEN && x86_64::is_valid_utf8(v) {
+            return Ok(());
+        }
+    }
+    validate_utf8_scalar(v)
+}
+
+/// Finds the first invalid sequence in `v`, one character at a time after ASCII runs.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub fn validate_utf8_scalar(v: &[u8]) -> Result<(), Utf8Error> {
     const ASCII_BS: usize = 2 * size_of::<usize>();
     const ALIGN_MASK: usize = size_of::<usize>() - 1;
 
@@ -52,57 +96,63 @@ pub fn is_valid_utf8(v: &[u8]) -> bool {
             continue;
         }
 
-        // Non-ASCII: validate the full UTF-8 sequence without per-byte OOB checks.
-        let w = utf8_char_width(first);
-        if w == 0 || index + w > len {
-            return false;
+        // Non-ASCII: validate the full UTF-8 sequence without per-byte OOB checks, reporting how
+        // much of it was valid like core does.
+        let start = index;
+        macro_rules! err {
+            ($error_len:expr) => {
+                return Err(Utf8Error { valid_up_to: start, error_len: $error_len })
+            };
+        }
+        macro_rules! next {
+            () => {{
+                index += 1;
+                if index >= len {
+                    err!(None)
+                }
+                // SAFETY: guarded by `index < len` above
+                unsafe { *v.get_unchecked(index) }
+            }};
         }
 
-        match w {
+        match utf8_char_width(first) {
             2 => {
-                // SAFETY: `index + w <= len` above guarantees in-bounds
-                let b1 = unsafe { *v.get_unchecked(index + 1) };
-                if !is_continuation(b1) {
-                    return false;
+                if !is_continuation(next!()) {
+                    err!(Some(1))
                 }
             }
             3 => {
-                let b1 = unsafe { *v.get_unchecked(index + 1) };
                 #[allow(clippy::unnested_or_patterns)]
-                match (first, b1) {
+                match (first, next!()) {
                     (0xE0, 0xA0..=0xBF)
                     | (0xE1..=0xEC, 0x80..=0xBF)
                     | (0xED, 0x80..=0x9F)
                     | (0xEE..=0xEF, 0x80..=0xBF) => {}
-                    _ => return false
+                    _ => err!(Some(1))
                 }
-                let b2 = unsafe { *v.get_unchecked(index + 2) };
-                if !is_continuation(b2) {
-                    return false;
+                if !is_continuation(next!()) {
+                    err!(Some(2))
                 }
             }
             4 => {
-                let b1 = unsafe { *v.get_unchecked(index + 1) };
-                match (first, b1) {
+                match (first, next!()) {
                     (0xF0, 0x90..=0xBF) | (0xF1..=0xF3, 0x80..=0xBF) | (0xF4, 0x80..=0x8F) => {}
-                    _ => return false
+                    _ => err!(Some(1))
                 }
-                let b2 = unsafe { *v.get_unchecked(index + 2) };
-                if !is_continuation(b2) {
-                    return false;
+                if !is_continuation(next!()) {
+                    err!(Some(2))
                 }
-                let b3 = unsafe { *v.get_unchecked(index + 3) };
-                if !is_continuation(b3) {
-                    return false;
+                if !is_continuation(next!()) {
+                    err!(Some(3))
                 }
             }
-            _ => return false
+            _ => err!(Some(1))
         }
 
-        index += w;
+        index += 1;
     }
 
-    true
+    Ok(())
 }
 
 #[must_use]
@@ -0,0 +1,491 @@
+// vectorized utf-8 validation for x86_64, using the lookup algorithm from simdjson/simdutf8
+//  (Keiser & Lemire, "Validating UTF-8 In Less Than One Instruction Per Byte"). each byte is
+//  checked against the one to three bytes before it, using the high and low nibbles of the previous
+//  byte and the high nibble of the current one to look up which errors the pair could be. a block
+//  of ASCII only has to check that the previous block didn't end in the middle of a sequence.
+//
+// the validators only say whether the bytes are valid; finding the error is left to the scalar one.
+//  with `std`, the widest one the cpu supports is picked the first time one is needed. without it,
+//  it's picked at compile time from the enabled target features, falling back to the scalar one.
+
+// without `std`, at most one of the validators is used outside of benchmarks
+#![cfg_attr(not(any(feature = "std", feature = "__bench")), allow(dead_code))]
+
+#[cfg(feature = "std")]
+import! {
+    {
+        is_x86_feature_detected,
+        sync::atomic::{AtomicU8, Ordering}
+    }
+}
+
+// shorter inputs are left to the scalar validator, whose word-at-a-time ASCII path is faster for
+//  them than setting up the vectors
+pub const MIN_LEN: usize = 32;
+
+// the errors each nibble lookup can indicate. a pair of bytes is invalid iff an error is in all
+//  three lookups, except for 3- and 4-byte sequences missing continuations, which are found by
+//  comparing against the bytes two and three back.
+const TOO_SHORT: u8 = 1 << 0;
+const TOO_LONG: u8 = 1 << 1;
+const OVERLONG_3: u8 = 1 << 2;
+const TOO_LARGE: u8 = 1 << 3;
+const SURROGATE: u8 = 1 << 4;
+const OVERLONG_2: u8 = 1 << 5;
+const TOO_LARGE_1000: u8 = 1 << 6;
+const OVERLONG_4: u8 = 1 << 6;
+const TWO_CONTS: u8 = 1 << 7;
+const CARRY: u8 = TOO_SHORT | TOO_LONG | TWO_CONTS;
+
+// indexed by the high nibble of the previous byte
+const BYTE_1_HIGH: [u8; 16] = [
+    // 0___ (ASCII)
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    // 10__ (continuation)
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    // 1100 (2-byte lead)
+    TOO_SHORT | OVERLONG_2,
+    // 1101
+    TOO_SHORT,
+    // 1110 (3-byte lead)
+    TOO_SHORT | OVERLONG_3 | SURROGATE,
+    // 1111 (4-byte lead)
+    TOO_SHORT | TOO_LARGE | TOO_LARGE_1000 | OVERLONG_4
+];
+
+// indexed by the low nibble of the previous byte
+const BYTE_1_LOW: [u8; 16] = [
+    CARRY | OVERLONG_3 | OVERLONG_2 | OVERLONG_4,
+    CARRY | OVERLONG_2,
+    CARRY,
+    CARRY,
+    CARRY | TOO_LARGE,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000 | SURROGATE,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000
+];
+
+// indexed by the high nibble of the current byte
+const BYTE_2_HIGH: [u8; 16] = [
+    // 0___ (ASCII)
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    // 1000
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE_1000 | OVERLONG_4,
+    // 1001
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE,
+    // 101_
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    // 11__ (lead)
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT
+];
+
+// the algorithm, shared by both widths. expects `V`, `N` and the helpers below in scope.
+macro_rules! validator {
+    ($feature:tt) => {
+        // the state carried between blocks
+        struct State {
+            prev: V,
+            prev_incomplete: V,
+            error: V
+        }
+
+        #[inline]
+        #[target_feature(enable = $feature)]
+        unsafe fn step(s: &mut State, input: V) {
+            if is_ascii(input) {
+                // only an error if the previous block ended in the middle of a sequence
+                s.error = or(s.error, s.prev_incomplete);
+            } else {
+                let prev1 = prev1(input, s.prev);
+                let special = and(
+                    and(
+                        lookup(shr4(prev1), &BYTE_1_HIGH),
+                        lookup(and(prev1, splat(0x0F)), &BYTE_1_LOW)
+                    ),
+                    lookup(shr4(input), &BYTE_2_HIGH)
+                );
+
+                // the bytes after a 3- or 4-byte lead must be continuations, and have been marked
+                //  TWO_CONTS by the lookups above, so the two must agree
+                let third = subs(prev2(input, s.prev), splat(0xE0 - 0x80));
+                let fourth = subs(prev3(input, s.prev), splat(0xF0 - 0x80));
+                let must_be_cont = and(or(third, fourth), splat(0x80));
+                s.error = or(s.error, xor(must_be_cont, special));
+
+                s.prev_incomplete = subs(input, incomplete_max());
+            }
+            s.prev = input;
+        }
+    };
+}
+
+/// Checks whether `v` is valid UTF-8 with the widest validator the CPU supports.
+#[cfg(feature = "std")]
+#[inline]
+pub fn is_valid_utf8(v: &[u8]) -> bool {
+    // SAFETY: each validator is only used once the cpu is known to support it
+    match IMPL.load(Ordering::Relaxed) {
+        AVX2 => unsafe { avx2::is_valid(v) },
+        SSE41 => unsafe { sse41::is_valid(v) },
+        SCALAR => super::validate_utf8_scalar(v).is_ok(),
+        _ => detect(v)
+    }
+}
+
+/// Checks whether `v` is valid UTF-8 with the widest validator the enabled target features allow.
+#[cfg(not(feature = "std"))]
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub fn is_valid_utf8(v: &[u8]) -> bool {
+    #[cfg(target_feature = "avx2")]
+    {
+        // SAFETY: the target supports avx2
+        unsafe { avx2::is_valid(v) }
+    }
+    #[cfg(all(target_feature = "sse4.1", not(target_feature = "avx2")))]
+    {
+        // SAFETY: the target supports sse4.1
+        unsafe { sse41::is_valid(v) }
+    }
+    #[cfg(not(target_feature = "sse4.1"))]
+    {
+        super::validate_utf8_scalar(v).is_ok()
+    }
+}
+
+#[cfg(feature = "std")]
+const UNDETECTED: u8 = 0;
+#[cfg(feature = "std")]
+const SCALAR: u8 = 1;
+#[cfg(feature = "std")]
+const SSE41: u8 = 2;
+#[cfg(feature = "std")]
+const AVX2: u8 = 3;
+
+#[cfg(feature = "std")]
+static IMPL: AtomicU8 = AtomicU8::new(UNDETECTED);
+
+#[cfg(feature = "std")]
+#[cold]
+#[inline(never)]
+fn detect(v: &[u8]) -> bool {
+    // racing threads all find the same answer
+    let imp = if is_x86_feature_detected!("avx2") {
+        AVX2
+    } else if is_x86_feature_detected!("sse4.1") {
+        SSE41
+    } else {
+        SCALAR
+    };
+    IMPL.store(imp, Ordering::Relaxed);
+    is_valid_utf8(v)
+}
+
+// validates `v` a block at a time, copying the last partial block into a zeroed buffer. zeroes are
+//  ASCII, so a sequence cut off by the end of `v` is still found.
+macro_rules! is_valid {
+    ($v:ident) => {{
+        let len = $v.len();
+        let p = $v.as_ptr();
+        let mut s = State { prev: splat(0), prev_incomplete: splat(0), error: splat(0) };
+
+        let mut i = 0;
+        while i + N <= len {
+            step(&mut s, load(p.add(i)));
+            i += N;
+        }
+        if i < len {
+            let mut buf = [0u8; N];
+            switch!(core::ptr::copy_nonoverlapping(p.add(i), buf.as_mut_ptr(), len - i));
+            step(&mut s, load(buf.as_ptr()));
+        }
+
+        !any(or(s.error, s.prev_incomplete))
+    }};
+}
+
+pub mod sse41 {
+    use {
+        super::{BYTE_1_HIGH, BYTE_1_LOW, BYTE_2_HIGH},
+        core::arch::x86_64::{
+            __m128i,
+            _mm_alignr_epi8,
+            _mm_and_si128,
+            _mm_loadu_si128,
+            _mm_movemask_epi8,
+            _mm_or_si128,
+            _mm_set1_epi8,
+            _mm_shuffle_epi8,
+            _mm_srli_epi16,
+            _mm_subs_epu8,
+            _mm_testz_si128,
+            _mm_xor_si128
+        }
+    };
+
+    type V = __m128i;
+    const N: usize = 16;
+
+    const INCOMPLETE_MAX: [u8; N] = [
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xEF, 0xDF,
+        0xBF
+    ];
+
+    /// Checks whether `v` is valid UTF-8, 16 bytes at a time.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support SSE4.1.
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn is_valid(v: &[u8]) -> bool {
+        is_valid!(v)
+    }
+
+    validator!("sse4.1");
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn load(p: *const u8) -> V {
+        _mm_loadu_si128(p.cast())
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    #[allow(clippy::cast_possible_wrap)]
+    unsafe fn splat(b: u8) -> V {
+        _mm_set1_epi8(b as i8)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn lookup(i: V, table: &[u8; 16]) -> V {
+        _mm_shuffle_epi8(load(table.as_ptr()), i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn incomplete_max() -> V {
+        load(INCOMPLETE_MAX.as_ptr())
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn shr4(v: V) -> V {
+        _mm_and_si128(_mm_srli_epi16(v, 4), splat(0x0F))
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn prev1(input: V, prev: V) -> V {
+        _mm_alignr_epi8(input, prev, 15)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn prev2(input: V, prev: V) -> V {
+        _mm_alignr_epi8(input, prev, 14)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn prev3(input: V, prev: V) -> V {
+        _mm_alignr_epi8(input, prev, 13)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn and(a: V, b: V) -> V {
+        _mm_and_si128(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn or(a: V, b: V) -> V {
+        _mm_or_si128(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn xor(a: V, b: V) -> V {
+        _mm_xor_si128(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn subs(a: V, b: V) -> V {
+        _mm_subs_epu8(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn is_ascii(v: V) -> bool {
+        _mm_movemask_epi8(v) == 0
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn any(v: V) -> bool {
+        _mm_testz_si128(v, v) == 0
+    }
+}
+
+pub mod avx2 {
+    use {
+        super::{BYTE_1_HIGH, BYTE_1_LOW, BYTE_2_HIGH},
+        core::arch::x86_64::{
+            __m256i,
+            _mm_loadu_si128,
+            _mm256_alignr_epi8,
+            _mm256_and_si256,
+            _mm256_broadcastsi128_si256,
+            _mm256_loadu_si256,
+            _mm256_movemask_epi8,
+            _mm256_or_si256,
+            _mm256_permute2x128_si256,
+            _mm256_set1_epi8,
+            _mm256_shuffle_epi8,
+            _mm256_srli_epi16,
+            _mm256_subs_epu8,
+            _mm256_testz_si256,
+            _mm256_xor_si256
+        }
+    };
+
+    type V = __m256i;
+    const N: usize = 32;
+
+    const INCOMPLETE_MAX: [u8; N] = [
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xEF,
+        0xDF, 0xBF
+    ];
+
+    /// Checks whether `v` is valid UTF-8, 32 bytes at a time.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn is_valid(v: &[u8]) -> bool {
+        is_valid!(v)
+    }
+
+    validator!("avx2");
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(p: *const u8) -> V {
+        _mm256_loadu_si256(p.cast())
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    #[allow(clippy::cast_possible_wrap)]
+    unsafe fn splat(b: u8) -> V {
+        _mm256_set1_epi8(b as i8)
+    }
+
+    // the shuffle is per 128-bit lane, so the table is in both
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn lookup(i: V, table: &[u8; 16]) -> V {
+        _mm256_shuffle_epi8(_mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr().cast())), i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn incomplete_max() -> V {
+        load(INCOMPLETE_MAX.as_ptr())
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shr4(v: V) -> V {
+        _mm256_and_si256(_mm256_srli_epi16(v, 4), splat(0x0F))
+    }
+
+    // alignr is also per lane, so the low lane of `input` is shifted in after the high lane of
+    //  `prev` and the high lane after the low lane of `input`
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev1(input: V, prev: V) -> V {
+        _mm256_alignr_epi8(input, _mm256_permute2x128_si256(prev, input, 0x21), 15)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev2(input: V, prev: V) -> V {
+        _mm256_alignr_epi8(input, _mm256_permute2x128_si256(prev, input, 0x21), 14)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev3(input: V, prev: V) -> V {
+        _mm256_alignr_epi8(input, _mm256_permute2x128_si256(prev, input, 0x21), 13)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn and(a: V, b: V) -> V {
+        _mm256_and_si256(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn or(a: V, b: V) -> V {
+        _mm256_or_si256(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn xor(a: V, b: V) -> V {
+        _mm256_xor_si256(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn subs(a: V, b: V) -> V {
+        _mm256_subs_epu8(a, b)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn is_ascii(v: V) -> bool {
+        _mm256_movemask_epi8(v) == 0
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn any(v: V) -> bool {
+        _mm256_testz_si256(v, v) == 0
+    }
+}
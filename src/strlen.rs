@@ -0,0 +1,21 @@
+// a pure-rust strlen, used instead of libc's with the `rust_strlen` feature so miri can see through
+//  it. it reads one byte at a time: whole words or vectors would be faster, but they extend past
+//  the terminator and out of the string's allocation, which is undefined behavior in rust even
+//  where it can't fault. doing that soundly needs `asm!`, which is above the msrv, so libc's stays
+//  the default.
+// TODO: make this const once raw pointer reads in const fns are allowed at the msrv (1.58)
+
+/// Gets the length of a C-style string by finding the first `\0` byte after the given pointer, one
+/// byte at a time.
+///
+/// # Safety
+///
+/// `s` must point to a null-terminated string.
+#[inline]
+pub unsafe fn strlen_bytes(s: *const u8) -> usize {
+    let mut i = 0;
+    while s.add(i).read() != 0 {
+        i += 1;
+    }
+    i
+}
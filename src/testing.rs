@@ -0,0 +1,170 @@
+//! Helpers for tests that fake the program's arguments.
+//!
+//! [`direct::set_argc_argv`](crate::direct::set_argc_argv) swaps `argc`/`argv` for the whole
+//! process, so tests using it race with each other when run in parallel. This module provides two
+//! ways around that:
+//!
+//! - [`ArgvGuard`] holds a process-wide lock while `argc`/`argv` are overridden, and restores the
+//!   previous values when dropped. [`ArgvGuard::lock`] only takes the lock, for tests reading the
+//!   real arguments.
+//! - [`ThreadArgvGuard`] overrides `argc`/`argv` for the current thread only. Everything that reads
+//!   the arguments through [`direct::argc_argv`](crate::direct::argc_argv) (such as
+//!   [`Args::new`](crate::Args::new), the [`MappedArgs`](crate::MappedArgs) constructors, and the
+//!   parsers) sees the override, so each test thread can use its own fake `argv` without locking.
+//!
+//! Requires the `testing` feature.
+
+import! {
+    {
+        cell::Cell,
+        ops::Drop,
+        option::Option::{self, None, Some},
+        sync::atomic::{AtomicBool, Ordering},
+        thread::yield_now
+    }
+}
+
+use crate::direct;
+
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+::std::thread_local! {
+    static OVERRIDE: Cell<Option<(u32, *const *const u8)>> = Cell::new(None);
+    // how many `ArgvGuard`s this thread holds, so nested ones don't wait on its own lock
+    static HELD: Cell<usize> = Cell::new(0);
+}
+
+fn lock() {
+    if HELD.with(|h| h.replace(h.get() + 1)) == 0 {
+        while LOCK.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            yield_now();
+        }
+    }
+}
+
+fn unlock() {
+    if HELD.with(|h| {
+        h.set(h.get() - 1);
+        h.get()
+    }) == 0
+    {
+        LOCK.store(false, Ordering::Release);
+    }
+}
+
+/// Gets the current thread's `(argc, argv)` override, if any.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+pub(crate) fn thread_override() -> Option<(u32, *const *const u8)> {
+    OVERRIDE.with(Cell::get)
+}
+
+/// A guard which overrides `argc`/`argv` for the whole process while holding a process-wide lock.
+/// The previous values are restored and the lock released when it is dropped, including when a
+/// test panics.
+///
+/// Only one thread can hold `ArgvGuard`s at a time. Guards on the same thread can be nested, as
+/// long as they are dropped in the reverse order of their creation.
+///
+/// # Examples
+///
+/// ```
+/// use snailx::{Args, testing::ArgvGuard};
+///
+/// let args = snailx::argv!["prog", "--flag"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let _guard = unsafe { ArgvGuard::new(args.len() as u32, args.as_ptr()) };
+///
+/// assert_eq!(Args::new().len(), 2);
+/// ```
+#[must_use = "the override is undone when the guard is dropped"]
+pub struct ArgvGuard {
+    prev: Option<(u32, *const *const u8)>
+}
+
+impl ArgvGuard {
+    /// Waits for the process-wide lock, then sets `argc` and `argv` via
+    /// [`direct::set_argc_argv`](crate::direct::set_argc_argv).
+    ///
+    /// # Safety
+    ///
+    /// `argv` must point to at least `argc` valid pointers to null-terminated strings, which must
+    /// remain valid until the guard is dropped. Code setting `argc`/`argv` without an `ArgvGuard`
+    /// is not synchronized by the lock.
+    pub unsafe fn new(argc: u32, argv: *const *const u8) -> ArgvGuard {
+        lock();
+
+        // resolve any lazy fallback first, so it is what gets restored rather than a null argv
+        let _ = direct::imp::argc_argv();
+        ArgvGuard { prev: Some(direct::set_argc_argv(argc, argv)) }
+    }
+
+    /// Waits for the process-wide lock without changing `argc`/`argv`, so the current values
+    /// (such as the real arguments) can't be overridden by other `ArgvGuard`s while it is held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snailx::{Args, testing::ArgvGuard};
+    ///
+    /// let _guard = ArgvGuard::lock();
+    /// assert_eq!(Args::new().len(), std::env::args_os().len());
+    /// ```
+    pub fn lock() -> ArgvGuard {
+        lock();
+        ArgvGuard { prev: None }
+    }
+}
+
+impl Drop for ArgvGuard {
+    fn drop(&mut self) {
+        if let Some((argc, argv)) = self.prev {
+            // SAFETY: these are the values which were set before this guard was created
+            unsafe {
+                direct::set_argc_argv(argc, argv);
+            }
+        }
+        unlock();
+    }
+}
+
+/// A guard which overrides `argc`/`argv` for the current thread only. The previous override (or
+/// lack thereof) is restored when it is dropped, so guards can be nested.
+///
+/// While an override is active, the process-wide values (including those set by [`ArgvGuard`]) are
+/// ignored on this thread.
+///
+/// # Examples
+///
+/// ```
+/// use snailx::{MappedArgs, testing::ThreadArgvGuard};
+///
+/// let args = snailx::argv!["prog", "input.txt"];
+/// // SAFETY: `argv!` only produces static, null-terminated strings
+/// let _guard = unsafe { ThreadArgvGuard::new(args.len() as u32, args.as_ptr()) };
+///
+/// assert_eq!(MappedArgs::utf8().nth(1), Some("input.txt"));
+/// ```
+#[must_use = "the override is undone when the guard is dropped"]
+pub struct ThreadArgvGuard {
+    prev: Option<(u32, *const *const u8)>
+}
+
+impl ThreadArgvGuard {
+    /// Overrides `argc` and `argv` for the current thread.
+    ///
+    /// # Safety
+    ///
+    /// `argv` must point to at least `argc` valid pointers to null-terminated strings, which must
+    /// remain valid until the guard is dropped.
+    pub unsafe fn new(argc: u32, argv: *const *const u8) -> ThreadArgvGuard {
+        ThreadArgvGuard { prev: OVERRIDE.with(|o| o.replace(Some((argc, argv)))) }
+    }
+}
+
+impl Drop for ThreadArgvGuard {
+    fn drop(&mut self) {
+        let prev = self.prev;
+        OVERRIDE.with(|o| o.set(prev));
+    }
+}